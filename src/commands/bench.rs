@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use log::info;
+use plonky2::field::types::Field;
+use rand::{CryptoRng, Rng, RngCore};
+
+use vfhe_plonky2::vtfhe::crypto::ggsw::Ggsw;
+use vfhe_plonky2::vtfhe::crypto::keys::{BootstrapKey, GlweSecretKey, KeySwitchKey};
+use vfhe_plonky2::vtfhe::crypto::lut::Lut;
+use vfhe_plonky2::vtfhe::crypto::lwe::get_delta;
+use vfhe_plonky2::vtfhe::crypto::poly::Poly;
+use vfhe_plonky2::vtfhe::ivc_based_vpbs::{
+    verify_pbs, PbsCircuitOptions, PbsInputs, PbsProver, PbsStatement,
+};
+use vfhe_plonky2::{n, p, C, D, ELL, F, K, LOGB, N, SIGMA_GLWE, SIGMA_LWE};
+
+// the numbers of steps per proof the prover is compiled for
+const SUPPORTED_STEPS_PER_PROOF: [usize; 4] = [1, 2, 4, 8];
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// numbers of PBS steps proven by each recursive proof to compare: 1, 2, 4 or 8
+    #[arg(long, num_args = 1.., default_values_t = [1, 2, 4])]
+    steps_per_proof: Vec<usize>,
+}
+
+// the measurements of the PBS proof with a number of steps per proof
+struct BenchResult {
+    steps_per_proof: usize,
+    degree_bits: usize,
+    num_proofs: usize,
+    build_time: Duration,
+    prove_time: Duration,
+}
+
+// Proves the same PBS with circuits that prove different numbers of steps per recursive proof,
+// and prints the size of each circuit and the time to build it and to prove the PBS, to find
+// the best trade-off between the size of the circuit and the number of recursions.
+pub fn run(args: Args, rng: &mut (impl RngCore + CryptoRng)) -> Result<()> {
+    if let Some(steps_per_proof) = args
+        .steps_per_proof
+        .iter()
+        .find(|steps_per_proof| !SUPPORTED_STEPS_PER_PROOF.contains(steps_per_proof))
+    {
+        bail!("unsupported number of steps per proof {steps_per_proof}, expected one of {SUPPORTED_STEPS_PER_PROOF:?}");
+    }
+
+    let s_to = GlweSecretKey::<F, D, N, K>::generate_partial::<n>(rng);
+    let s_lwe = s_to.to_lwe_key::<n>();
+    let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+    let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, SIGMA_GLWE, rng);
+    let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_to, SIGMA_LWE, rng);
+    let delta = get_delta::<F, D>(2 * p);
+    let testv = Lut::Identity.testv(p, delta);
+    let m = F::from_canonical_usize(rng.gen_range(0..p));
+    let ct = s_lwe.encrypt(&(delta * m), SIGMA_LWE, rng);
+
+    let results: Vec<BenchResult> = args
+        .steps_per_proof
+        .iter()
+        .map(|&steps_per_proof| {
+            info!("benchmarking {steps_per_proof} steps per proof");
+            match steps_per_proof {
+                1 => bench::<1>(&ct, &testv, bsk.ggsws(), ksk.ggsw()),
+                2 => bench::<2>(&ct, &testv, bsk.ggsws(), ksk.ggsw()),
+                4 => bench::<4>(&ct, &testv, bsk.ggsws(), ksk.ggsw()),
+                _ => bench::<8>(&ct, &testv, bsk.ggsws(), ksk.ggsw()),
+            }
+        })
+        .collect::<Result<_>>()?;
+
+    for result in results {
+        println!("{} steps per proof", result.steps_per_proof);
+        println!("  circuit degree: 2^{}", result.degree_bits);
+        println!("  recursive proofs: {}", result.num_proofs);
+        println!("  circuit build time: {:.1?}", result.build_time);
+        println!("  proving time: {:.1?}", result.prove_time);
+    }
+    Ok(())
+}
+
+fn bench<const STEPS_PER_PROOF: usize>(
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+    ksk: &Ggsw<F, D, N, K, ELL>,
+) -> Result<BenchResult> {
+    let start = Instant::now();
+    let prover = PbsProver::<F, C, D, n, N, K, ELL, LOGB, STEPS_PER_PROOF>::new();
+    let build_time = start.elapsed();
+
+    let start = Instant::now();
+    let inputs = PbsInputs::new(ct, testv, bsk, ksk);
+    let (outputs, proof) = prover.prove(&inputs)?;
+    let prove_time = start.elapsed();
+    verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+        PbsCircuitOptions::default(),
+        &outputs,
+        &PbsStatement::new::<n, ELL>(&inputs).digest(),
+        &proof,
+        &prover.circuit_data.verifier_data(),
+    )?;
+
+    Ok(BenchResult {
+        steps_per_proof: STEPS_PER_PROOF,
+        degree_bits: prover.circuit_data.common.degree_bits(),
+        num_proofs: (n + 2).div_ceil(STEPS_PER_PROOF),
+        build_time,
+        prove_time,
+    })
+}
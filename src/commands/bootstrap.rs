@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::info;
+
+use vfhe_plonky2::vtfhe::crypto::pbs;
+use vfhe_plonky2::{n, ParameterSetId, D, ELL, F, K, LOGB, N};
+
+use super::files::{read_inputs, write_outputs, BootstrapOutputs};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// the ciphertext, test vector and keys of the PBS
+    #[arg(long, default_value = "bootstrap_inputs.json")]
+    inputs: PathBuf,
+    /// where to write the output ciphertext
+    #[arg(long, default_value = "bootstrap_outputs.json")]
+    outputs: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let inputs = read_inputs(&args.inputs)?;
+
+    // perform the PBS natively, without proving it
+    info!("Parameters: n={n}, N={N}, k={}, logB={LOGB}, ell={ELL}", K - 1);
+    let out_ct = pbs::<F, D, n, N, K, ELL, LOGB>(
+        &inputs.ct,
+        &inputs.testv,
+        &inputs.bsk,
+        &inputs.ksk,
+    );
+
+    // there is no circuit data without a proof
+    let outputs = BootstrapOutputs {
+        params: ParameterSetId,
+        out_ct,
+        cd: None,
+    };
+    write_outputs(&args.outputs, &outputs)?;
+
+    info!("outputs written to {}", args.outputs.display());
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::{info, warn};
+use plonky2::field::types::{Field, PrimeField64};
+
+use vfhe_plonky2::vtfhe::crypto::lut::Lut;
+use vfhe_plonky2::vtfhe::crypto::lwe::get_delta;
+use vfhe_plonky2::{p, D, F};
+
+use super::files::{read_json, read_outputs, Secrets};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// LUT evaluated by the PBS (defaults to the one recorded in the secrets)
+    #[arg(long)]
+    lut: Option<Lut>,
+    /// the secret key and plaintext
+    #[arg(long, default_value = "secrets.json")]
+    secrets: PathBuf,
+    /// the output ciphertext
+    #[arg(long, default_value = "bootstrap_outputs.json")]
+    outputs: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let delta = get_delta::<F, D>(2 * p);
+
+    let outputs = read_outputs(&args.outputs)?;
+    let out_ct = outputs.out_ct;
+
+    // Read the secret key s_to and message m
+    let secrets: Secrets = read_json(&args.secrets)?;
+    let m = F::from_canonical_u64(secrets.m);
+
+    // LUT evaluated by the PBS: given on the command line, else the one recorded by `vfhe encrypt`
+    let lut: Lut = match args.lut {
+        Some(lut) => lut,
+        None => secrets.lut.as_deref().map_or(Ok(Lut::Identity), str::parse)?,
+    };
+    let f_m = F::from_canonical_usize(lut.eval(secrets.m as usize, p));
+
+    let dec_out_ct_coeffs = out_ct.decrypt(&secrets.s_to).coeffs;
+    let dec_out_ct = F::from_canonical_usize(
+        ((dec_out_ct_coeffs[0].to_canonical_u64() as f64) / (delta.to_canonical_u64() as f64)).round() as usize
+            % (2 * p),
+    );
+
+    info!("plaintext: {m} {}(plaintext): {f_m} dec(output_ciphertext): {dec_out_ct}", lut.name());
+    if dec_out_ct != f_m {
+        warn!("the output ciphertext does not decrypt to {}(plaintext)", lut.name());
+    }
+    Ok(())
+}
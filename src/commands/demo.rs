@@ -0,0 +1,58 @@
+use anyhow::Result;
+use log::info;
+use plonky2::field::types::{Field, PrimeField64};
+use rand::{CryptoRng, Rng, RngCore};
+
+use vfhe_plonky2::vtfhe::crypto::keys::{BootstrapKey, GlweSecretKey, KeySwitchKey};
+use vfhe_plonky2::vtfhe::crypto::lut::Lut;
+use vfhe_plonky2::vtfhe::crypto::lwe::get_delta;
+use vfhe_plonky2::vtfhe::ivc_based_vpbs::{
+    verified_pbs, verify_pbs, PbsCircuitOptions, PbsInputs, PbsStatement,
+};
+use vfhe_plonky2::{n, p, C, D, ELL, F, K, LOGB, N, SIGMA_GLWE, SIGMA_LWE};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// LUT evaluated by the PBS: identity, not, negate, double or square
+    #[arg(long, default_value = "identity")]
+    lut: Lut,
+}
+
+pub fn run(args: Args, rng: &mut (impl RngCore + CryptoRng)) -> Result<()> {
+    // partial GLWE key corresponding to LWE key
+    let s_to = GlweSecretKey::<F, D, N, K>::generate_partial::<n>(rng);
+    let s_lwe = s_to.to_lwe_key::<n>();
+    info!("s_lwe: {:?}", s_lwe.elements());
+
+    let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+    let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, SIGMA_GLWE, rng);
+    let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_to, SIGMA_LWE, rng);
+
+    let delta = get_delta::<F, D>(2 * p);
+    let testv = args.lut.testv(p, delta);
+    let m = F::from_canonical_usize(rng.gen_range(0..p));
+    let ct = s_lwe.encrypt(&(delta * m), SIGMA_LWE, rng);
+
+    // prove a PBS
+    let options = PbsCircuitOptions::default();
+    let inputs = PbsInputs::new(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+    let (outputs, proof, cd) = verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+        options,
+        &inputs,
+        Some(&s_glwe),
+        Some(&s_lwe),
+        Some(&s_to),
+    )?;
+
+    // verify the PBS
+    let digest = PbsStatement::new::<n, ELL>(&inputs).digest();
+    verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(options, &outputs, &digest, &proof, &cd.verifier_data())?;
+    let m_bar = s_to.decrypt(&outputs.into_glwe()?).coeffs;
+
+    let m_out = F::from_canonical_usize(
+        ((m_bar[0].to_canonical_u64() as f64) / (delta.to_canonical_u64() as f64)).round() as usize
+            % (2 * p),
+    );
+    info!("in: {m} {}(in): {} out: {m_out}", args.lut.name(), args.lut.eval(m.to_canonical_u64() as usize, p));
+    Ok(())
+}
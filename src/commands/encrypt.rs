@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::info;
+use plonky2::field::types::{Field, PrimeField64};
+use rand::{CryptoRng, Rng, RngCore};
+
+use vfhe_plonky2::vtfhe::crypto::keys::{BootstrapKey, GlweSecretKey, KeySwitchKey};
+use vfhe_plonky2::vtfhe::crypto::lut::Lut;
+use vfhe_plonky2::vtfhe::crypto::lwe::get_delta;
+use vfhe_plonky2::vtfhe::crypto::poly::Poly;
+use vfhe_plonky2::vtfhe::ivc_based_vpbs::{PbsInputs, PbsStatement};
+use vfhe_plonky2::{n, p, ParameterSetId, D, ELL, F, K, LOGB, N, SIGMA_GLWE, SIGMA_LWE};
+
+use super::files::{write_inputs, write_json, BootstrapInputs, Secrets, StatementDigest};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// LUT evaluated by the PBS: identity, not, negate, double or square
+    #[arg(long, default_value = "identity")]
+    lut: Lut,
+    /// where to write the secret key and plaintext
+    #[arg(long, default_value = "secrets.json")]
+    secrets: PathBuf,
+    /// where to write the ciphertext, test vector and keys of the PBS
+    #[arg(long, default_value = "bootstrap_inputs.json")]
+    inputs: PathBuf,
+    /// where to write the digest of the PBS statement
+    #[arg(long, default_value = "statement_digest.json")]
+    digest: PathBuf,
+    /// write the bootstrapping key seed-compressed, with its masks regenerated from a seed per
+    /// GGSW: the inputs are then about K times smaller
+    #[arg(long)]
+    compress: bool,
+}
+
+pub fn run(args: Args, rng: &mut (impl RngCore + CryptoRng)) -> Result<()> {
+    let lut = args.lut;
+    info!("LUT: {}", lut.name());
+
+    // partial GLWE key corresponding to LWE key
+    let s_to = GlweSecretKey::<F, D, N, K>::generate_partial::<n>(rng);
+    let s_lwe = s_to.to_lwe_key::<n>();
+    info!("s_lwe: {:?}", s_lwe.elements());
+
+    let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+    let compressed_bsk = args.compress.then(|| {
+        BootstrapKey::<F, D, n, N, K, ELL>::generate_compressed::<LOGB>(&s_lwe, &s_glwe, SIGMA_GLWE, rng)
+    });
+    let bsk = match &compressed_bsk {
+        Some(compressed_bsk) => BootstrapKey::decompress(compressed_bsk),
+        None => BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, SIGMA_GLWE, rng),
+    };
+    let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_to, SIGMA_LWE, rng);
+
+    let delta = get_delta::<F, D>(2 * p);
+    let testv: Poly<F, D, N> = lut.testv(p, delta);
+    let m = F::from_canonical_usize(rng.gen_range(0..p));
+    let ct: Vec<F> = s_lwe.encrypt(&(delta * m), SIGMA_LWE, rng);
+
+    // Write the secret values m and s_to
+    let secrets = Secrets {
+        params: ParameterSetId,
+        m: m.to_canonical_u64(),
+        lut: Some(lut.name().to_string()),
+        s_to: s_to.into_polys(),
+    };
+    write_json(&args.secrets, &secrets)?;
+    info!("secret values written to {}", args.secrets.display());
+
+    // Publish the digest of the PBS statement, which suffices to verify a proof of the PBS
+    let statement = PbsStatement::<F, D, N, K>::new::<n, ELL>(&PbsInputs::new(
+        &ct,
+        &testv,
+        bsk.ggsws(),
+        ksk.ggsw(),
+    ));
+    let digest = StatementDigest {
+        params: ParameterSetId,
+        digest: statement.digest().elements.to_vec(),
+    };
+
+    // Write the proof inputs
+    let inputs = BootstrapInputs {
+        params: ParameterSetId,
+        ct,
+        testv,
+        // the compressed key replaces the full one in the file
+        bsk: if compressed_bsk.is_some() { Vec::new() } else { bsk.into_ggsws() },
+        compressed_bsk,
+        ksk: ksk.into_ggsw(),
+        lut: Some(lut.name().to_string()),
+    };
+    write_inputs(&args.inputs, &inputs)?;
+    info!("inputs written to {}", args.inputs.display());
+
+    write_json(&args.digest, &digest)?;
+    info!("statement digest written to {}", args.digest.display());
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{ensure, Context, Result};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use serde::de::{DeserializeOwned, Deserializer};
+use serde::{Deserialize, Serialize};
+
+use vfhe_plonky2::vtfhe::crypto::binary::{BinaryReader, BinaryWriter, RecordKind};
+use vfhe_plonky2::vtfhe::crypto::compressed::CompressedGgsw;
+use vfhe_plonky2::vtfhe::crypto::ggsw::Ggsw;
+use vfhe_plonky2::vtfhe::crypto::glwe::Glwe;
+use vfhe_plonky2::vtfhe::crypto::poly::Poly;
+use vfhe_plonky2::vtfhe::crypto::serialization::{
+    deserialize_elements, deserialize_vec, serialize_elements,
+};
+use vfhe_plonky2::{n, ParameterSetId, C, D, ELL, F, K, N, PARAMS};
+
+// The files exchanged between the parties. Field elements are written as canonical u64s, in
+// the same layout as the Sindri `InputData` in lib.rs (see `crypto::serialization`). The (large)
+// bootstrap inputs and outputs are written in the binary container format instead if their path
+// ends in `.bin`. Every file records the id of the parameter set it was written for (see
+// `ParameterSetId`), as do the proofs and their checkpoints.
+// The bootstrapping key of the bootstrap inputs may be seed-compressed, in which case it is
+// decompressed when the inputs are read.
+
+// written by the data owner, read by anybody performing or verifying the PBS
+#[derive(Serialize, Deserialize)]
+pub struct BootstrapInputs {
+    pub params: ParameterSetId,
+    #[serde(
+        serialize_with = "serialize_elements",
+        deserialize_with = "deserialize_elements::<_, _, { n + 1 }>"
+    )]
+    pub ct: Vec<F>,
+    pub testv: Poly<F, D, N>,
+    // empty if the inputs hold the compressed key instead
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_vec::<_, _, n>"
+    )]
+    pub bsk: Vec<Ggsw<F, D, N, K, ELL>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_compressed_bsk"
+    )]
+    pub compressed_bsk: Option<Vec<CompressedGgsw<F, D, N, K, ELL>>>,
+    pub ksk: Ggsw<F, D, N, K, ELL>,
+    // name of the LUT evaluated by the PBS, see `Lut`
+    #[serde(default)]
+    pub lut: Option<String>,
+}
+
+fn deserialize_compressed_bsk<'de, De: Deserializer<'de>>(
+    deserializer: De,
+) -> Result<Option<Vec<CompressedGgsw<F, D, N, K, ELL>>>, De::Error> {
+    deserialize_vec::<_, _, n>(deserializer).map(Some)
+}
+
+impl BootstrapInputs {
+    // replaces a compressed bootstrapping key by the key it decompresses to
+    fn decompress_bsk(mut self) -> Result<Self> {
+        if let Some(compressed_bsk) = self.compressed_bsk.take() {
+            ensure!(self.bsk.is_empty(), "both bsk and compressed_bsk are given");
+            self.bsk = compressed_bsk.iter().map(|ggsw| ggsw.decompress().ntt_forward()).collect();
+        }
+        ensure!(self.bsk.len() == n, "bsk: expected {n} items, found {}", self.bsk.len());
+        Ok(self)
+    }
+}
+
+// written by the compute node
+#[derive(Serialize, Deserialize)]
+pub struct BootstrapOutputs {
+    pub params: ParameterSetId,
+    pub out_ct: Glwe<F, D, N, K>,
+    // verifier circuit data, only written along with a local proof
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cd: Option<Vec<u8>>,
+}
+
+// written by the compute node along with the outputs, if the proof is local
+#[derive(Serialize, Deserialize)]
+pub struct BootstrapProof {
+    pub params: ParameterSetId,
+    pub proof: ProofWithPublicInputs<F, C, D>,
+}
+
+// kept by the data owner
+#[derive(Serialize, Deserialize)]
+pub struct Secrets {
+    pub params: ParameterSetId,
+    pub m: u64,
+    #[serde(default)]
+    pub lut: Option<String>,
+    pub s_to: Vec<Poly<F, D, N>>,
+}
+
+// published by the data owner, enough to verify a proof of the PBS
+#[derive(Serialize, Deserialize)]
+pub struct StatementDigest {
+    pub params: ParameterSetId,
+    #[serde(
+        serialize_with = "serialize_elements",
+        deserialize_with = "deserialize_elements::<_, _, 4>"
+    )]
+    pub digest: Vec<F>,
+}
+
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    // report where in the file a malformed value is, e.g. `bsk[3][1][0][1]: expected ...`
+    serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&contents))
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    fs::write(path, serde_json::to_string(value)?)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn is_binary(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "bin")
+}
+
+fn binary_reader(path: &Path) -> Result<BinaryReader<BufReader<File>>> {
+    let file = File::open(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(BinaryReader::new(BufReader::new(file), &PARAMS))
+}
+
+fn binary_writer(path: &Path) -> Result<BinaryWriter<BufWriter<File>>> {
+    let file = File::create(path).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(BinaryWriter::new(BufWriter::new(file), &PARAMS))
+}
+
+// binary layout: ct, testv, bsk or compressed bsk, ksk, LUT name (empty if not recorded)
+pub fn read_inputs(path: &Path) -> Result<BootstrapInputs> {
+    let inputs: BootstrapInputs = if !is_binary(path) {
+        read_json(path)?
+    } else {
+        let mut r = binary_reader(path)?;
+        let read = |r: &mut BinaryReader<_>| -> Result<BootstrapInputs> {
+            let ct = r.read_lwe()?;
+            let testv = r.read_poly()?;
+            let (bsk, compressed_bsk) = match r.peek_kind()? {
+                RecordKind::CompressedGgsws => (Vec::new(), Some(r.read_compressed_bsk()?)),
+                _ => (r.read_bsk()?, None),
+            };
+            Ok(BootstrapInputs {
+                params: ParameterSetId,
+                ct,
+                testv,
+                bsk,
+                compressed_bsk,
+                ksk: r.read_ggsw()?,
+                lut: Some(String::from_utf8(r.read_bytes()?)?).filter(|lut| !lut.is_empty()),
+            })
+        };
+        read(&mut r).with_context(|| format!("failed to parse {}", path.display()))?
+    };
+    inputs
+        .decompress_bsk()
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+pub fn write_inputs(path: &Path, inputs: &BootstrapInputs) -> Result<()> {
+    if !is_binary(path) {
+        return write_json(path, inputs);
+    }
+    let mut w = binary_writer(path)?;
+    w.write_lwe::<F>(&inputs.ct)?;
+    w.write_poly::<F, D, N>(&inputs.testv)?;
+    match &inputs.compressed_bsk {
+        Some(compressed_bsk) => w.write_compressed_bsk::<F, D, N, K, ELL>(compressed_bsk)?,
+        None => w.write_bsk::<F, D, N, K, ELL>(&inputs.bsk)?,
+    }
+    w.write_ggsw::<F, D, N, K, ELL>(&inputs.ksk)?;
+    w.write_bytes(inputs.lut.as_deref().unwrap_or_default().as_bytes())?;
+    w.into_inner().flush()?;
+    Ok(())
+}
+
+// binary layout: out_ct, verifier circuit data (empty if there is no proof)
+pub fn read_outputs(path: &Path) -> Result<BootstrapOutputs> {
+    if !is_binary(path) {
+        return read_json(path);
+    }
+    let mut r = binary_reader(path)?;
+    let read = |r: &mut BinaryReader<_>| -> Result<BootstrapOutputs> {
+        Ok(BootstrapOutputs {
+            params: ParameterSetId,
+            out_ct: r.read_glwe()?,
+            cd: Some(r.read_bytes()?).filter(|cd| !cd.is_empty()),
+        })
+    };
+    read(&mut r).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+pub fn write_outputs(path: &Path, outputs: &BootstrapOutputs) -> Result<()> {
+    if !is_binary(path) {
+        return write_json(path, outputs);
+    }
+    let mut w = binary_writer(path)?;
+    w.write_glwe::<F, D, N, K>(&outputs.out_ct)?;
+    w.write_bytes(outputs.cd.as_deref().unwrap_or_default())?;
+    w.into_inner().flush()?;
+    Ok(())
+}
@@ -0,0 +1,14 @@
+// One module per subcommand of `vfhe`, each with its command line `Args` and a `run` function.
+// The parties exchange data through the files in `files`, whose paths are all configurable.
+
+pub mod bench;
+pub mod bootstrap;
+pub mod decrypt;
+pub mod demo;
+pub mod encrypt;
+pub mod noise;
+pub mod prove_local;
+pub mod prove_sindri;
+pub mod verify;
+
+mod files;
@@ -0,0 +1,32 @@
+use anyhow::Result;
+
+use vfhe_plonky2::vtfhe::crypto::noise::PbsNoise;
+use vfhe_plonky2::vtfhe::crypto::parameter_set::{ParameterSet, PARAMETER_SETS};
+use vfhe_plonky2::{F, PARAMS};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// estimate only this parameter set (defaults to all of them)
+    #[arg(long)]
+    params: Option<String>,
+}
+
+// Prints the estimated noise of the PBS and its failure probability, to compare parameter sets
+// without running the PBS. The standard deviations are relative to the field order.
+pub fn run(args: Args) -> Result<()> {
+    let sets = match args.params {
+        Some(id) => vec![ParameterSet::by_id(&id)?],
+        None => PARAMETER_SETS.to_vec(),
+    };
+    for params in sets {
+        params.check()?;
+        let noise = PbsNoise::estimate::<F>(&params);
+        let current = if params == PARAMS { " (current)" } else { "" };
+        println!("{}{current}", params.id);
+        println!("  mod switch std:     {:.3e}", noise.mod_switch.sqrt());
+        println!("  blind rotation std: {:.3e}", noise.blind_rotation.sqrt());
+        println!("  output std:         {:.3e}", noise.output.sqrt());
+        println!("  failure probability per PBS: 2^{:.1}", noise.log2_failure_probability);
+    }
+    Ok(())
+}
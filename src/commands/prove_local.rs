@@ -0,0 +1,212 @@
+use std::fs;
+use std::io::stdout;
+use std::iter::once;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use anyhow::{ensure, Result};
+use log::info;
+use plonky2::util::serialization::DefaultGateSerializer;
+
+use vfhe_plonky2::vtfhe::ivc_based_vpbs::{
+    CheckpointConfig, PbsInputs, PbsJob, PbsProver, PbsStatement,
+};
+use vfhe_plonky2::vtfhe::progress::{JsonLinesProgress, NoProgress};
+use vfhe_plonky2::{n, ParameterSetId, C, D, ELL, F, K, LOGB, N, PARAMS};
+
+use super::files::{
+    read_inputs, write_json, write_outputs, BootstrapInputs, BootstrapOutputs, BootstrapProof,
+};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// the ciphertext, test vector and keys of the PBS; several inputs are proven as independent
+    /// jobs (see `--jobs`), which must all have the same keys
+    #[arg(long, num_args = 1.., default_value = "bootstrap_inputs.json")]
+    inputs: Vec<PathBuf>,
+    /// where to write the output ciphertext and verifier circuit data, one path per input
+    #[arg(long, num_args = 1.., default_value = "bootstrap_outputs.json")]
+    outputs: Vec<PathBuf>,
+    /// where to write the proof, one path per input
+    #[arg(long, num_args = 1.., default_value = "bootstrap_proof.json")]
+    proof: Vec<PathBuf>,
+    /// where to persist the latest proof while proving, removed once the proof is complete
+    #[arg(long, default_value = "bootstrap_checkpoint.json")]
+    checkpoint: PathBuf,
+    /// number of steps between two checkpoints
+    #[arg(long, default_value = "50")]
+    checkpoint_interval: NonZeroUsize,
+    /// continue from the checkpoint of an interrupted run instead of starting over
+    #[arg(long)]
+    resume: bool,
+    /// load the proving circuit from this file if it exists, else build it and save it there
+    #[arg(long)]
+    circuit: Option<PathBuf>,
+    /// number of inputs proven in parallel, each of which holds its proof in memory
+    #[arg(long, default_value = "1")]
+    jobs: NonZeroUsize,
+    /// how to report the progress after each step: a progress bar on stderr, a line of JSON per
+    /// step on stdout, or nothing
+    #[arg(long, value_enum, default_value = "bar")]
+    progress: Progress,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Progress {
+    Bar,
+    Json,
+    None,
+}
+
+type Prover = PbsProver<F, C, D, n, N, K, ELL, LOGB>;
+
+pub fn run(args: Args) -> Result<()> {
+    ensure!(
+        args.outputs.len() == args.inputs.len() && args.proof.len() == args.inputs.len(),
+        "expected one output and one proof path per input, found {} inputs, {} outputs and {} proofs",
+        args.inputs.len(),
+        args.outputs.len(),
+        args.proof.len()
+    );
+    ensure!(
+        !args.resume || args.inputs.len() == 1,
+        "--resume only applies to the proof of a single input"
+    );
+    let inputs = read_inputs(&args.inputs[0])?;
+
+    let mut prover = match &args.circuit {
+        Some(path) if path.exists() => {
+            info!("loading the circuit from {}", path.display());
+            Prover::load(path)?
+        }
+        circuit => {
+            let prover = Prover::new();
+            if let Some(path) = circuit {
+                prover.save(path)?;
+                info!("circuit written to {}", path.display());
+            }
+            prover
+        }
+    };
+    match args.progress {
+        Progress::Bar => {}
+        Progress::Json => prover.set_progress(JsonLinesProgress::new(stdout())),
+        Progress::None => prover.set_progress(NoProgress),
+    }
+    // Get the VerifierCircuitData of cd, because the prover_only part is huge
+    let verifier_cd_bytes = prover.circuit_data.verifier_data().to_bytes(&DefaultGateSerializer).unwrap();
+
+    if args.inputs.len() == 1 {
+        prove_one(&args, &prover, inputs, verifier_cd_bytes)
+    } else {
+        prove_jobs(&args, &prover, inputs, &verifier_cd_bytes)
+    }
+}
+
+fn prove_one(
+    args: &Args,
+    prover: &Prover,
+    inputs: BootstrapInputs,
+    verifier_cd_bytes: Vec<u8>,
+) -> Result<()> {
+    // prove a PBS, checkpointing the latest proof so that a crash does not lose all the steps
+    let checkpoint_config = CheckpointConfig {
+        path: args.checkpoint.clone(),
+        interval: args.checkpoint_interval,
+        params: PARAMS.id,
+    };
+    let pbs_inputs = PbsInputs::new(&inputs.ct, &inputs.testv, &inputs.bsk, &inputs.ksk);
+    let (pbs_outputs, proof) = if args.resume {
+        prover.resume(&pbs_inputs, &checkpoint_config)?
+    } else {
+        prover.prove_checkpointed(&pbs_inputs, &checkpoint_config)?
+    };
+
+    let proof = BootstrapProof {
+        params: ParameterSetId,
+        proof,
+    };
+    write_json(&args.proof[0], &proof)?;
+
+    let outputs = BootstrapOutputs {
+        params: ParameterSetId,
+        out_ct: pbs_outputs.into_glwe()?,
+        cd: Some(verifier_cd_bytes),
+    };
+    write_outputs(&args.outputs[0], &outputs)?;
+
+    if checkpoint_config.path.exists() {
+        fs::remove_file(&checkpoint_config.path)?;
+    }
+
+    info!(
+        "outputs written to {}; proof written to {}",
+        args.outputs[0].display(),
+        args.proof[0].display()
+    );
+    Ok(())
+}
+
+// Proves the PBSs of several inputs with the same keys on `args.jobs` threads. The inputs after
+// the first are only read by the worker that picks up their path, and only the keys of the
+// first stay in memory: the keys of the others are compared to them by their hashes in the PBS
+// statement, and dropped.
+fn prove_jobs(
+    args: &Args,
+    prover: &Prover,
+    first_inputs: BootstrapInputs,
+    verifier_cd_bytes: &[u8],
+) -> Result<()> {
+    let (first_path, other_paths) = args.inputs.split_first().unwrap();
+    let BootstrapInputs {
+        ct, testv, bsk, ksk, ..
+    } = first_inputs;
+    let key_hashes = |inputs: &PbsInputs<F, D, N, K, ELL>| {
+        let statement = PbsStatement::new::<n, ELL>(inputs);
+        (statement.bsk_hash, statement.ksk_hash)
+    };
+    let first_key_hashes = key_hashes(&PbsInputs::new(&ct, &testv, &bsk, &ksk));
+    // the first job is already read
+    let jobs = once(None).chain(other_paths.iter().map(Some));
+    let load_job = |path: Option<&PathBuf>| {
+        let Some(path) = path else {
+            return Ok(PbsJob {
+                ct: ct.clone(),
+                testv: testv.clone(),
+            });
+        };
+        let inputs = read_inputs(path)?;
+        ensure!(
+            key_hashes(&PbsInputs::new(&inputs.ct, &inputs.testv, &inputs.bsk, &inputs.ksk))
+                == first_key_hashes,
+            "{} does not have the same keys as {}",
+            path.display(),
+            first_path.display()
+        );
+        Ok(PbsJob {
+            ct: inputs.ct,
+            testv: inputs.testv,
+        })
+    };
+
+    info!("proving {} PBSs with {} workers", args.inputs.len(), args.jobs);
+    prover.prove_many(jobs, load_job, &bsk, &ksk, args.jobs, |index, out_ct, proof| {
+        let proof = BootstrapProof {
+            params: ParameterSetId,
+            proof,
+        };
+        write_json(&args.proof[index], &proof)?;
+        let outputs = BootstrapOutputs {
+            params: ParameterSetId,
+            out_ct,
+            cd: Some(verifier_cd_bytes.to_vec()),
+        };
+        write_outputs(&args.outputs[index], &outputs)?;
+        info!(
+            "job {index}: outputs written to {}; proof written to {}",
+            args.outputs[index].display(),
+            args.proof[index].display()
+        );
+        Ok(())
+    })
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use log::info;
+
+use vfhe_plonky2::{InputData, ParameterSetId};
+
+use super::files::{read_inputs, read_outputs, write_json};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// the ciphertext, test vector and keys of the PBS
+    #[arg(long, default_value = "bootstrap_inputs.json")]
+    inputs: PathBuf,
+    /// the output ciphertext
+    #[arg(long, default_value = "bootstrap_outputs.json")]
+    outputs: PathBuf,
+    /// where to write the input of the Sindri proving service
+    #[arg(long, default_value = "sindri_input.json")]
+    sindri_input: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let inputs = read_inputs(&args.inputs)?;
+    let outputs = read_outputs(&args.outputs)?;
+
+    // Construct an InputData struct with the necessary fields
+    let input_data = InputData {
+        params: ParameterSetId,
+        ct: inputs.ct,
+        testv: inputs.testv,
+        bsk: inputs.bsk,
+        ksk: inputs.ksk,
+        out_ct: outputs.out_ct,
+    };
+    write_json(&args.sindri_input, &input_data)?;
+
+    info!(
+        "Bootstrap inputs and output written to {}",
+        args.sindri_input.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use log::info;
+use plonky2::hash::hash_types::HashOut;
+use plonky2::plonk::circuit_data::{CommonCircuitData, VerifierCircuitData, VerifierOnlyCircuitData};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use plonky2::util::serialization::DefaultGateSerializer;
+use serde::Deserialize;
+use serde_json::Value;
+
+use vfhe_plonky2::vtfhe::crypto::lut::Lut;
+use vfhe_plonky2::vtfhe::crypto::lwe::get_delta;
+use vfhe_plonky2::vtfhe::crypto::poly::Poly;
+use vfhe_plonky2::vtfhe::ivc_based_vpbs::{
+    verify_pbs, PbsCircuitOptions, PbsInputs, PbsOutputs, PbsStatement,
+};
+use vfhe_plonky2::{n, p, C, D, ELL, F, K, LOGB, N};
+
+use super::files::{read_inputs, read_json, read_outputs, BootstrapProof, StatementDigest};
+
+#[derive(Deserialize, Debug)]
+pub struct JsonProofData {
+    pub proof: String,
+    pub common: String,
+    pub verifier_data: String,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// LUT the PBS is expected to evaluate (defaults to the one recorded in the inputs)
+    #[arg(long)]
+    lut: Option<Lut>,
+    /// the ciphertext, test vector and keys of the PBS
+    #[arg(long, default_value = "bootstrap_inputs.json")]
+    inputs: PathBuf,
+    /// the digest of the PBS statement, used if the inputs are not present
+    #[arg(long, default_value = "statement_digest.json")]
+    digest: PathBuf,
+    /// the output ciphertext (and verifier circuit data of a local proof)
+    #[arg(long, default_value = "bootstrap_outputs.json")]
+    outputs: PathBuf,
+    /// a proof generated locally, verified if present
+    #[arg(long, default_value = "bootstrap_proof.json")]
+    proof: PathBuf,
+    /// a proof generated by Sindri, verified if present
+    #[arg(long, default_value = "sindri_proof.json")]
+    sindri_proof: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    // The statement of the PBS (input ciphertext, test vector and keys) is recomputed from the
+    // bootstrap inputs; a verifier who does not hold them can instead use the digest that
+    // `vfhe encrypt` published
+    let digest: HashOut<F> = if args.inputs.exists() {
+        statement_digest(&args.inputs, args.lut)?
+    } else if args.digest.exists() {
+        let digest: StatementDigest = read_json(&args.digest)?;
+        HashOut::try_from(digest.digest.as_slice())?
+    } else {
+        bail!(
+            "neither {} nor {} found",
+            args.inputs.display(),
+            args.digest.display()
+        );
+    };
+    info!("statement digest: {:?}", digest.elements);
+
+    let outputs = read_outputs(&args.outputs)?;
+    let pbs_outputs = PbsOutputs {
+        glwes: vec![outputs.out_ct],
+        lwe: None,
+    };
+    let options = PbsCircuitOptions::default();
+
+    // If there is a local proof, then we will verify it
+    if args.proof.exists() {
+        info!("Starting verification of [{}]", args.proof.display());
+        // the circuit data is only written along with a local proof
+        let verifier_cd_bytes = outputs.cd.with_context(|| {
+            format!(
+                "{} should contain the circuit data written by `vfhe prove-local`",
+                args.outputs.display()
+            )
+        })?;
+
+        let vcd: VerifierCircuitData<F, C, D> =
+            VerifierCircuitData::from_bytes(verifier_cd_bytes, &DefaultGateSerializer).map_err(|e| {
+                anyhow!("invalid circuit data in {}: {e}", args.outputs.display())
+            })?;
+
+        let BootstrapProof { proof, .. } = read_json(&args.proof)?;
+
+        // verify the PBS
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(options, &pbs_outputs, &digest, &proof, &vcd)
+            .with_context(|| format!("{} does not verify", args.proof.display()))?;
+        info!("verification successful!");
+    }
+
+    // If there is a Sindri proof, then we will verify it
+    if args.sindri_proof.exists() {
+        info!("Verifying proof from file: [{}]", args.sindri_proof.display());
+        let proof_details: Value = read_json(&args.sindri_proof)?;
+        let path = args.sindri_proof.display();
+        let proof_object = proof_details
+            .as_object()
+            .with_context(|| format!("{path} should contain valid proof data"))?;
+
+        let proof_data: JsonProofData = if proof_object.contains_key("proof") {
+            serde_json::from_value(proof_details["proof"].clone())
+        } else {
+            serde_json::from_value(proof_details.clone())
+        }
+        .with_context(|| format!("invalid proof data in {path}"))?;
+
+        let decode = |field: &str, data: &str| {
+            general_purpose::STANDARD
+                .decode(data)
+                .with_context(|| format!("invalid base64 in the {field} of {path}"))
+        };
+        let proof_bytes = decode("proof", &proof_data.proof)?;
+        let common_bytes = decode("common", &proof_data.common)?;
+        let verifier_only_bytes = decode("verifier_data", &proof_data.verifier_data)?;
+
+        let default_gate_serializer = DefaultGateSerializer;
+
+        let common = CommonCircuitData::<F, D>::from_bytes(common_bytes, &default_gate_serializer)
+            .map_err(|e| anyhow!("invalid common circuit data in {path}: {e}"))?;
+        let proof = ProofWithPublicInputs::<F, C, D>::from_bytes(proof_bytes, &common)
+            .map_err(|e| anyhow!("invalid proof in {path}: {e}"))?;
+        let verifier_data = VerifierOnlyCircuitData::<C, D>::from_bytes(verifier_only_bytes)
+            .map_err(|e| anyhow!("invalid verifier data in {path}: {e}"))?;
+
+        let verifier: VerifierCircuitData<F, C, D> = VerifierCircuitData {
+            verifier_only: verifier_data,
+            common,
+        };
+
+        // verify the PBS
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(options, &pbs_outputs, &digest, &proof, &verifier)
+            .with_context(|| format!("{path} does not verify"))?;
+        info!("verification successful!");
+    }
+    Ok(())
+}
+
+// The testv is recomputed from the LUT, so that the proof (whose acc_init is the rotated testv)
+// attests which function was evaluated; the LUT is either given or taken from the inputs
+// (defaults to identity)
+fn statement_digest(inputs_path: &Path, lut: Option<Lut>) -> Result<HashOut<F>> {
+    let inputs = read_inputs(inputs_path)?;
+    let lut: Lut = match lut {
+        Some(lut) => lut,
+        None => inputs.lut.as_deref().map_or(Ok(Lut::Identity), str::parse)?,
+    };
+    let testv: Poly<F, D, N> = lut.testv(p, get_delta::<F, D>(2 * p));
+    ensure!(
+        inputs.testv == testv,
+        "the testv in {} is not the test vector of LUT '{}'",
+        inputs_path.display(),
+        lut.name()
+    );
+    info!("verifying a PBS of LUT '{}'", lut.name());
+
+    let statement = PbsStatement::<F, D, N, K>::new::<n, ELL>(&PbsInputs::new(
+        &inputs.ct,
+        &testv,
+        &inputs.bsk,
+        &inputs.ksk,
+    ));
+    Ok(statement.digest())
+}
@@ -1,270 +1,86 @@
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 
-use ntt::params::N;
-
 use crate::vtfhe::crypto::ggsw::Ggsw;
 use crate::vtfhe::crypto::glwe::Glwe;
-use crate::vtfhe::ivc_based_vpbs::{verified_pbs};
+use crate::vtfhe::crypto::parameter_set::{ParameterSet, PARAM_128_BIT_P2};
+use crate::vtfhe::crypto::serialization::{deserialize_elements, deserialize_vec, serialize_elements};
+use crate::vtfhe::ivc_based_vpbs::{verified_pbs, PbsCircuitOptions, PbsInputs};
+use anyhow::{Context, Result};
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
-use std::convert::TryInto;
 use plonky2::plonk::proof::ProofWithPublicInputs;
 use plonky2::plonk::circuit_data::VerifierOnlyCircuitData;
 use plonky2::plonk::circuit_data::CommonCircuitData;
-use plonky2::field::types::{Field, PrimeField64};
 
 use std::fs;
 use crate::vtfhe::crypto::poly::Poly;
-use crate::vtfhe::crypto::glev::Glev;
-mod ntt;
-mod vec_arithmetic;
-mod vtfhe;
-
-const LOGB: usize = 5;
-const ELL: usize = 4;
-
-const K: usize = 2; // GLWE dimension (K = k + 1)
-const n: usize = 728; // LWE dimension
-const p: usize = 2; // plaintext modulus
-
-// plonky2 parameters
-pub const D: usize = 2;
-pub type C = PoseidonGoldilocksConfig;
-pub type F = <C as GenericConfig<D>>::F;
-
-pub struct InputDataCT {
-    pub ct: Vec<F>,
-}
-
-impl Serialize for InputDataCT {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        self.ct.serialize(serializer)
-    }
-}
-
-impl<'de> Deserialize<'de> for InputDataCT {
-    fn deserialize<D>(deserializer: D) -> Result<InputDataCT, D::Error>
-    where
-        D: serde::Deserializer<'de> {
-        let ct: Vec<F> = Vec::deserialize(deserializer)?;
-        Ok(InputDataCT { ct })
-    }
-}
-
-pub struct InputDataPoly {
-    pub poly: Poly<F, D, N>
-}
-
-impl Serialize for InputDataPoly {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        self.poly.coeffs.to_vec().serialize(serializer)                           
-    }
-}
-
-impl<'de> Deserialize<'de> for InputDataPoly {
-    fn deserialize<D>(deserializer: D) -> Result<InputDataPoly, D::Error>
-    where
-        D: serde::Deserializer<'de> {
-        let poly_coeffs: Vec<F> = Vec::deserialize(deserializer)?;
-        let poly = Poly { coeffs: poly_coeffs.try_into().unwrap() };
-        Ok(InputDataPoly { poly })
-    }
-}
-
-pub struct InputDataBSK {
-    pub bsk: Vec<Ggsw<F, D, N, K, ELL>>
-}
-
-impl Serialize for InputDataBSK {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let bsk_ser : Vec<Vec<Vec<Vec<Vec<u64>>>>> = self.bsk
-        .iter()
-        .map(
-            |ggsw|
-                ggsw.glevs.iter()
-                .map(|glev|
-                    glev.glwes.iter()
-                    .map(|glwe|
-                        glwe.polys.iter()
-                        .map(|poly|
-                            poly.coeffs.iter()
-                            .map(|x| x.to_canonical_u64())
-                            .collect::<Vec<u64>>()
-                        )
-                        .collect::<Vec<Vec<u64>>>()
-                    )
-                    .collect::<Vec<Vec<Vec<u64>>>>()
-                )
-                .collect::<Vec<Vec<Vec<Vec<u64>>>>>()
-        )
-        .collect::<Vec<Vec<Vec<Vec<Vec<u64>>>>>>();
-        bsk_ser.serialize(serializer)
-    }
-}
-
-impl<'de> Deserialize<'de> for InputDataBSK {
-    fn deserialize<DD>(deserializer: DD) -> Result<InputDataBSK, DD::Error>
-    where
-        DD: serde::Deserializer<'de> {
-        let bsk_ser: Vec<Vec<Vec<Vec<Vec<u64>>>>> = Vec::deserialize(deserializer)?;
-        let bsk: Vec<Ggsw<F, D, N, K, ELL>> = bsk_ser
-            .iter()
-            .map(
-                |ggsw| 
-                    Ggsw {
-                        glevs: ggsw.iter().map(
-                            |glev| 
-                                Glev {
-                                    glwes: glev.iter().map(
-                                        |glwe| 
-                                            Glwe {
-                                                polys: glwe.iter().map(
-                                                    |poly| 
-                                                        Poly {
-                                                            coeffs: poly.iter().map(
-                                                                |x| F::from_canonical_u64(*x)
-                                                            ).collect::<Vec<F>>().try_into().unwrap()
-                                                        }
-                                                ).collect::<Vec<Poly<F, D, N>>>().try_into().unwrap()
-                                            }
-                                    ).collect::<Vec<Glwe<F, D, N, K>>>().try_into().unwrap()
-                                }
-                        ).collect::<Vec<Glev<F, D, N, K, ELL>>>().try_into().unwrap()
-                    }
-            )
-            .collect();
-
-        Ok(InputDataBSK { bsk })
+pub mod ntt;
+pub mod vec_arithmetic;
+pub mod vtfhe;
+
+// the parameter set of the CLI (see `parameter_set`), e.g. the optimized parameters
+pub const PARAMS: ParameterSet = PARAM_128_BIT_P2;
+
+// decomposition parameters
+pub const LOGB: usize = PARAMS.log_base;
+pub const ELL: usize = PARAMS.num_limbs;
+
+pub const N: usize = PARAMS.ring_dim; // ring dimension
+pub const K: usize = PARAMS.glwe_dim; // GLWE dimension (K = k + 1)
+pub const n: usize = PARAMS.lwe_dim; // LWE dimension
+pub const p: usize = PARAMS.plaintext_modulus; // plaintext modulus
+pub const SIGMA_GLWE: f64 = PARAMS.sigma_glwe; // GLWE noise
+pub const SIGMA_LWE: f64 = PARAMS.sigma_lwe; // LWE noise
+
+// The id of `PARAMS`, recorded in the files written for it; a file written for another parameter
+// set is rejected when it is read
+#[derive(Clone, Copy, Default)]
+pub struct ParameterSetId;
+
+impl Serialize for ParameterSetId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(PARAMS.id)
     }
 }
 
-pub struct InputDataKSK {
-    pub ksk: Ggsw<F, D, N, K, ELL>
-}
-
-impl Serialize for InputDataKSK {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let ksk_ser : Vec<Vec<Vec<Vec<u64>>>>
-            = self.ksk.glevs.iter()
-            .map(|glev|
-                glev.glwes.iter()
-                .map(|glwe|
-                    glwe.polys.iter()
-                    .map(|poly|
-                        poly.coeffs.iter()
-                        .map(|x| x.to_canonical_u64())
-                        .collect::<Vec<u64>>()
-                    )
-                    .collect::<Vec<Vec<u64>>>()
-                )
-                .collect::<Vec<Vec<Vec<u64>>>>()
-            )
-            .collect::<Vec<Vec<Vec<Vec<u64>>>>>();
-        ksk_ser.serialize(serializer)
-    }
-}
-
-impl<'de> Deserialize<'de> for InputDataKSK {
-    fn deserialize<DD>(deserializer: DD) -> Result<InputDataKSK, DD::Error>
-    where
-        DD: serde::Deserializer<'de> {
-        let ksk_ser: Vec<Vec<Vec<Vec<u64>>>>
-            = Vec::deserialize(deserializer)?;
-        let ksk: Ggsw<F, D, N, K, ELL> = Ggsw {
-            glevs: ksk_ser.iter()
-            .map(|glev|
-                Glev {
-                    glwes: glev.iter()
-                    .map(|glwe|
-                        Glwe {
-                            polys: glwe.iter()
-                            .map(|poly|
-                                Poly {
-                                    coeffs: poly.iter()
-                                    .map(|x| F::from_canonical_u64(*x))
-                                    .collect::<Vec<F>>()
-                                    .try_into()
-                                    .unwrap()
-                                }
-                            )
-                            .collect::<Vec<Poly<F, D, N>>>()
-                            .try_into()
-                            .unwrap()
-                        }
-                    )
-                    .collect::<Vec<Glwe<F, D, N, K>>>()
-                    .try_into()
-                    .unwrap()
-                }
-            )
-            .collect::<Vec<Glev<F, D, N, K, ELL>>>()
-            .try_into()
-            .unwrap()
-        };
-        Ok(InputDataKSK { ksk })
-    }
-}
-
-pub struct InputDataGlwe {
-    pub glwe: Glwe<F, D, N, K>
-}
-
-impl Serialize for InputDataGlwe {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let out_ct_coeffs_vec : Vec<Vec<u64>> = self.glwe.polys.iter().map(|poly| poly.coeffs.iter().map(|x| x.to_canonical_u64()).collect()).collect();
-        out_ct_coeffs_vec.serialize(serializer)
+impl<'de> Deserialize<'de> for ParameterSetId {
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let id = String::deserialize(deserializer)?;
+        if id != PARAMS.id {
+            return Err(de::Error::custom(format!(
+                "written for the parameter set {id}, expected {}",
+                PARAMS.id
+            )));
+        }
+        Ok(ParameterSetId)
     }
 }
 
-impl<'de> Deserialize<'de> for InputDataGlwe {
-    fn deserialize<DD>(deserializer: DD) -> Result<InputDataGlwe, DD::Error>
-    where
-        DD: serde::Deserializer<'de> {
-        let out_ct_coeffs_vec : Vec<Vec<u64>> = Vec::deserialize(deserializer)?;
-        let out_ct_polys: [Poly<F, D, N>; K] = out_ct_coeffs_vec
-            .iter()
-            .map(|poly|
-                Poly {
-                    coeffs: poly.iter().map(|x| F::from_canonical_u64(*x)).collect::<Vec<F>>().try_into().unwrap()
-                }
-            )
-            .collect::<Vec<Poly<F, D, N>>>()
-            .try_into()
-            .unwrap();
-        let out_ct: Glwe<F, D, N, K> = Glwe { polys: out_ct_polys };
-        Ok(InputDataGlwe { glwe: out_ct })
-    }
-}
+// plonky2 parameters
+pub const D: usize = 2;
+pub type C = PoseidonGoldilocksConfig;
+pub type F = <C as GenericConfig<D>>::F;
 
 // The user configures the InputData struct with the necessary fields.
 #[derive(Serialize, Deserialize)]
 pub struct InputData {
-    pub ct: InputDataCT,
-    pub testv: InputDataPoly,
-    pub bsk: InputDataBSK,
-    pub ksk: InputDataKSK,
-    pub out_ct: InputDataGlwe,
+    pub params: ParameterSetId,
+    #[serde(serialize_with = "serialize_elements", deserialize_with = "deserialize_elements::<_, _, { n + 1 }>")]
+    pub ct: Vec<F>,
+    pub testv: Poly<F, D, N>,
+    #[serde(deserialize_with = "deserialize_vec::<_, _, n>")]
+    pub bsk: Vec<Ggsw<F, D, N, K, ELL>>,
+    pub ksk: Ggsw<F, D, N, K, ELL>,
+    pub out_ct: Glwe<F, D, N, K>,
 }
 
 // At minimum, we need to implement a method to read the input data from a JSON file.
 impl InputData {
-    pub fn from_json(path: &str) -> Self {
-        let contents = fs::read_to_string(path).expect("Something went wrong reading the file");
-        serde_json::from_str(&contents).unwrap()
+    pub fn from_json(path: &str) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+        serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(&contents))
+            .with_context(|| format!("failed to parse {path}"))
     }
 }
 
@@ -281,16 +97,19 @@ pub struct BootstrapCircuit {
 // the partial witness, and proving the circuit.
 impl BootstrapCircuit {
     pub fn prove(path: &str) -> Self {
-        let input_data = InputData::from_json(path);
+        let input_data = InputData::from_json(path).expect("failed to read the input data");
 
 
         // prove a PBS
-        let (out_ct, proof, cd) =
-            verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(&input_data.ct.ct, &input_data.testv.poly, &input_data.bsk.bsk, &input_data.ksk.ksk, None, None, None);
+        let inputs = PbsInputs::new(&input_data.ct, &input_data.testv, &input_data.bsk, &input_data.ksk);
+        let (outputs, proof, cd) =
+            verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(PbsCircuitOptions::default(), &inputs, None, None, None)
+                .expect("failed to prove the PBS");
+        let out_ct = outputs.into_glwe().expect("failed to prove the PBS");
 
         // FIXME: This should be done within the circuit building code
-        // verify that input_data.out_ct.glwe == out_ct
-        assert_eq!(input_data.out_ct.glwe, out_ct);
+        // verify that input_data.out_ct == out_ct
+        assert_eq!(input_data.out_ct, out_ct);
 
         BootstrapCircuit {
             proof,
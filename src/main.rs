@@ -1,67 +1,62 @@
 use anyhow::Result;
-use log::{info, LevelFilter};
-use plonky2::field::types::{Field, PrimeField64};
-use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
-use rand::random;
-
-use ntt::params::N;
-
-use crate::vtfhe::crypto::ggsw::Ggsw;
-use crate::vtfhe::crypto::glwe::Glwe;
-use crate::vtfhe::crypto::lwe::{encrypt, get_delta};
-use crate::vtfhe::crypto::{compute_bsk, get_testv};
-use crate::vtfhe::ivc_based_vpbs::{verified_pbs, verify_pbs};
+use clap::{Parser, Subcommand};
+use log::LevelFilter;
+
+use vfhe_plonky2::vtfhe::crypto::rng::new_rng;
+use vfhe_plonky2::PARAMS;
+
+mod commands;
+
+#[derive(Parser)]
+#[command(name = "vfhe", about = "Verifiable FHE: a verifiable TFHE bootstrapping with plonky2")]
+struct Cli {
+    /// log level: off, error, warn, info, debug or trace
+    #[arg(long, global = true, default_value = "debug")]
+    log_level: LevelFilter,
+    /// seed for the randomness of key generation and encryption, for reproducible runs
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+    #[command(subcommand)]
+    command: Command,
+}
 
-mod ntt;
-mod vec_arithmetic;
-mod vtfhe;
+#[derive(Subcommand)]
+enum Command {
+    /// create keys, plaintext, and ciphertext, and write to files
+    Encrypt(commands::encrypt::Args),
+    /// perform just the bootstrapping locally, without a proof
+    Bootstrap(commands::bootstrap::Args),
+    /// perform bootstrapping and generate proof of correctness locally
+    ProveLocal(commands::prove_local::Args),
+    /// prepare the inputs for proof generation by Sindri
+    ProveSindri(commands::prove_sindri::Args),
+    /// verify a proof generated locally or remotely
+    Verify(commands::verify::Args),
+    /// read keys and ciphertext, and decrypt
+    Decrypt(commands::decrypt::Args),
+    /// run all the steps in memory: encrypt, prove, verify and decrypt
+    Demo(commands::demo::Args),
+    /// estimate the noise and failure probability of the PBS for each parameter set
+    Noise(commands::noise::Args),
+    /// compare the proving time of the PBS for several numbers of steps per recursive proof
+    Bench(commands::bench::Args),
+}
 
 fn main() -> Result<()> {
-    // optimized parameters, use N=1024 (see ntt/mod.rs)
-
-    // dcecomposition parameters
-    const LOGB: usize = 5;
-    const ELL: usize = 4;
-
-    const K: usize = 2; // GLWE dimension (K = k + 1)
-    const n: usize = 728; // LWE dimension
-    const p: usize = 2; // plaintext modulus
-    let sigma_glwe = 4.99027217501041e-8; // GLWE noise
-    let sigma_lwe = 0.0000117021618159313; // LWE noise
-
-    // plonky2 parameters
-    const D: usize = 2;
-    type C = PoseidonGoldilocksConfig;
-    type F = <C as GenericConfig<D>>::F;
-
-    simple_logging::log_to_stderr(LevelFilter::Debug);
-
-    // partial GLWE key corresponding to LWE key
-    let s_to = Glwe::<F, D, N, K>::partial_key(n);
-    let s_lwe = Glwe::<F, D, N, K>::flatten_partial_key(&s_to, n);
-    info!("s_lwe: {:?}", s_lwe);
-
-    let s_glwe = Glwe::<F, D, N, K>::key_gen();
-    let bsk = compute_bsk::<F, D, N, K, ELL, LOGB>(&s_lwe, &s_glwe, sigma_glwe);
-    let ksk = Ggsw::<F, D, N, K, ELL>::compute_ksk::<LOGB>(&s_to, &s_glwe, sigma_lwe);
-    
-    let delta = get_delta::<F, D>(2 * p);
-    let testv = get_testv(p, delta);
-    let m = F::from_canonical_usize(random::<usize>() % p);
-    let ct = encrypt::<F, D, n>(&s_lwe, &(delta * m), sigma_lwe);
-
-    // prove a PBS
-    let (out_ct, proof, cd) =
-        verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(&ct, &testv, &bsk, &ksk, Some(&s_glwe), Some(&s_lwe), Some(&s_to));
-
-    // verify the PBS
-    verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(&out_ct, &ct, &testv, &bsk, &ksk, &proof, &cd.verifier_data());
-    let m_bar = out_ct.decrypt(&s_to).coeffs;
-
-    let m_out = F::from_canonical_usize(
-        ((m_bar[0].to_canonical_u64() as f64) / (delta.to_canonical_u64() as f64)).round() as usize
-            % (2 * p),
-    );
-    info!("in: {m} out: {m_out}");
-    Ok(())
+    let cli = Cli::parse();
+    simple_logging::log_to_stderr(cli.log_level);
+    PARAMS.check()?;
+    let mut rng = new_rng(cli.seed);
+
+    match cli.command {
+        Command::Encrypt(args) => commands::encrypt::run(args, &mut rng),
+        Command::Bootstrap(args) => commands::bootstrap::run(args),
+        Command::ProveLocal(args) => commands::prove_local::run(args),
+        Command::ProveSindri(args) => commands::prove_sindri::run(args),
+        Command::Verify(args) => commands::verify::run(args),
+        Command::Decrypt(args) => commands::decrypt::run(args),
+        Command::Demo(args) => commands::demo::run(args, &mut rng),
+        Command::Noise(args) => commands::noise::run(args),
+        Command::Bench(args) => commands::bench::run(args, &mut rng),
+    }
 }
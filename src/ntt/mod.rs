@@ -1,21 +1,77 @@
 use plonky2::field::extension::Extendable;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::Field64;
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 
-// use this path to set the ring dimension N (i.e. for N=512 set the path to "params_512.rs")
-// make sure to adjust the value of circuit_size in line 57 of "ivc_based_vpbs.rs" if
-// using a value other than 8 or 1024
-#[path = "params_1024.rs"]
-pub mod params;
+pub mod params_8;
+pub mod params_16;
+pub mod params_32;
+pub mod params_64;
+pub mod params_128;
+pub mod params_256;
+pub mod params_512;
+pub mod params_1024;
+pub mod params_2048;
+
+// the ring dimension N of the tests (the CLI uses that of its parameter set, see lib.rs); the
+// NTTs themselves take N as a const generic, so several ring dimensions can be used side by side
+pub use params_1024 as params;
+
+pub mod twiddles;
+
+// The tables of the negacyclic NTT of a ring dimension (see `gen_param_file.sage`)
+pub struct NttParams {
+    pub logn: u32,
+    pub ninv: u64,
+    pub roots: &'static [u64],
+    pub invroots: &'static [u64],
+    pub testg: &'static [u64],
+    pub testghat: &'static [u64],
+}
 
-fn ntt_fw_update<F: RichField + Extendable<D>, const D: usize>(cb: &mut  CircuitBuilder<F, D>, input: &Vec<Target>, m: usize) -> Vec<Target> {
-    let mut a = input.clone();
-    let t = params::N / (2 * m);
+macro_rules! ntt_params {
+    ($params:ident) => {
+        &NttParams {
+            logn: $params::LOGN,
+            ninv: $params::NINV,
+            roots: &$params::ROOTS,
+            invroots: &$params::INVROOTS,
+            testg: &$params::TESTG,
+            testghat: &$params::TESTGHAT,
+        }
+    };
+}
+
+// Returns the NTT tables of the ring dimension N over F: the precomputed ones if F is the
+// Goldilocks field and there is a `params_N.rs` file, else computed on first use (see
+// `twiddles`)
+pub fn ntt_params<F: RichField, const N: usize>() -> &'static NttParams {
+    if F::ORDER != GoldilocksField::ORDER {
+        return twiddles::computed_ntt_params::<F>(N);
+    }
+    match N {
+        8 => ntt_params!(params_8),
+        16 => ntt_params!(params_16),
+        32 => ntt_params!(params_32),
+        64 => ntt_params!(params_64),
+        128 => ntt_params!(params_128),
+        256 => ntt_params!(params_256),
+        512 => ntt_params!(params_512),
+        1024 => ntt_params!(params_1024),
+        2048 => ntt_params!(params_2048),
+        _ => twiddles::computed_ntt_params::<F>(N),
+    }
+}
+
+fn ntt_fw_update<F: RichField + Extendable<D>, const D: usize>(cb: &mut  CircuitBuilder<F, D>, input: &[Target], roots: &[u64], m: usize) -> Vec<Target> {
+    let mut a = input.to_vec();
+    let t = input.len() / (2 * m);
     for i in 0..m {
         let j1 = 2 * i * t;
         let j2 = j1 + t;
-        let root = params::ROOTS[m + i];
+        let root = roots[m + i];
         let s = cb.constant(F::from_canonical_u64(root));
         for j in j1..j2 {
             let u = a[j];
@@ -27,24 +83,26 @@ fn ntt_fw_update<F: RichField + Extendable<D>, const D: usize>(cb: &mut  Circuit
     a
 }
 
-pub fn ntt_forward<F: RichField + Extendable<D>, const D: usize>(cb: &mut  CircuitBuilder<F, D>, input: &Vec<Target>) -> Vec<Target>
+pub fn ntt_forward<F: RichField + Extendable<D>, const D: usize, const N: usize>(cb: &mut  CircuitBuilder<F, D>, input: &[Target]) -> Vec<Target>
 {
-    let mut current = input.clone();
-    for m in (0..params::LOGN).map(|i| {2usize.pow(i)}) {
-        current = ntt_fw_update(cb, &current, m);
+    assert_eq!(input.len(), N);
+    let params = ntt_params::<F, N>();
+    let mut current = input.to_vec();
+    for m in (0..params.logn).map(|i| {2usize.pow(i)}) {
+        current = ntt_fw_update(cb, &current, params.roots, m);
     }
 
     current
 }
 
 
-fn ntt_bw_update<F: RichField + Extendable<D>, const D: usize>(cb: &mut  CircuitBuilder<F, D>, input: &Vec<Target>, m: usize) -> Vec<Target> {
-    let mut a = input.clone();
-    let t = params::N / (2 * m);
+fn ntt_bw_update<F: RichField + Extendable<D>, const D: usize>(cb: &mut  CircuitBuilder<F, D>, input: &[Target], invroots: &[u64], m: usize) -> Vec<Target> {
+    let mut a = input.to_vec();
+    let t = input.len() / (2 * m);
     let mut j1 = 0usize;
     for i in 0..m {
         let j2 = j1 + t;
-        let root = params::INVROOTS[m + i];
+        let root = invroots[m + i];
         let s = cb.constant(F::from_canonical_u64(root));
         for j in j1..j2 {
             let u = a[j];
@@ -58,14 +116,16 @@ fn ntt_bw_update<F: RichField + Extendable<D>, const D: usize>(cb: &mut  Circuit
     a
 }
 
-pub fn ntt_backward<F: RichField + Extendable<D>, const D: usize>(cb: &mut  CircuitBuilder<F, D>, input: &Vec<Target>) -> Vec<Target>
+pub fn ntt_backward<F: RichField + Extendable<D>, const D: usize, const N: usize>(cb: &mut  CircuitBuilder<F, D>, input: &[Target]) -> Vec<Target>
 {
-    let mut current = input.clone();
-    for m in (0..params::LOGN).rev().map(|i| {2usize.pow(i)}) {
-        current = ntt_bw_update(cb, &current, m);
+    assert_eq!(input.len(), N);
+    let params = ntt_params::<F, N>();
+    let mut current = input.to_vec();
+    for m in (0..params.logn).rev().map(|i| {2usize.pow(i)}) {
+        current = ntt_bw_update(cb, &current, params.invroots, m);
     }
 
-    let n_inv = cb.constant(F::from_canonical_u64(params::NINV));
+    let n_inv = cb.constant(F::from_canonical_u64(params.ninv));
     current.into_iter().map(|g|{cb.mul(g, n_inv)}).collect()
 }
 
@@ -78,28 +138,32 @@ mod tests {
     use plonky2::plonk::circuit_data::CircuitConfig;
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 
-    #[test]
-    fn test_ntt_forward() {
-        const D: usize = 2;
-        type C = PoseidonGoldilocksConfig;
-        type F = <C as GenericConfig<D>>::F;
-        let N = params::N;
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
 
+    // proves the NTT of `input` (forward or backward) and checks it against `expected`
+    fn check_ntt<const N: usize>(forward: bool, input: &[u64], expected: &[u64]) {
         let config = CircuitConfig::standard_recursion_config();
         let mut builder = CircuitBuilder::<F, D>::new(config);
         let x = builder.add_virtual_targets(N);
 
-        let z = ntt_forward(&mut builder, &x);
+        let z = if forward {
+            ntt_forward::<F, D, N>(&mut builder, &x)
+        } else {
+            ntt_backward::<F, D, N>(&mut builder, &x)
+        };
+        // Public inputs are the initial value (provided below) and the result (which is generated).
         builder.register_public_inputs(&x);
         builder.register_public_inputs(&z);
         let mut pw = PartialWitness::new();
-        pw.set_target_arr(&x, &params::TESTG.map(|g| {F::from_canonical_u64(g)}));
+        pw.set_target_arr(&x, &input.iter().map(|&g| {F::from_canonical_u64(g)}).collect::<Vec<_>>());
 
         let data = builder.build::<C>();
         let proof = data.prove(pw).unwrap();
         let out = &proof.public_inputs[N..2 * N];
 
-        for (&actual, expected ) in out.into_iter().zip(params::TESTGHAT) {
+        for (&actual, &expected ) in out.iter().zip(expected) {
             assert_eq!(actual, F::from_canonical_u64(expected));
         }
 
@@ -107,31 +171,16 @@ mod tests {
     }
 
     #[test]
-    fn test_ntt_backward() {
-        const D: usize = 2;
-        type C = PoseidonGoldilocksConfig;
-        type F = <C as GenericConfig<D>>::F;
-        let N = params::N;
-
-        let config = CircuitConfig::standard_recursion_config();
-        let mut builder = CircuitBuilder::<F, D>::new(config);
-        let x = builder.add_virtual_targets(N);
-
-        let z = ntt_backward(&mut builder, &x);
-        // Public inputs are the initial value (provided below) and the result (which is generated).
-        builder.register_public_inputs(&x);
-        builder.register_public_inputs(&z);
-        let mut pw = PartialWitness::new();
-        pw.set_target_arr(&x, &params::TESTGHAT.map(|g| {F::from_canonical_u64(g)}));
-
-        let data = builder.build::<C>();
-        let proof = data.prove(pw).unwrap();
-        let out = &proof.public_inputs[N..2 * N];
-
-        for (&actual, expected ) in out.into_iter().zip(params::TESTG) {
-            assert_eq!(actual, F::from_canonical_u64(expected));
-        }
+    fn test_ntt_forward() {
+        check_ntt::<8>(true, ntt_params::<F, 8>().testg, ntt_params::<F, 8>().testghat);
+        check_ntt::<64>(true, ntt_params::<F, 64>().testg, ntt_params::<F, 64>().testghat);
+        check_ntt::<{ params::N }>(true, &params::TESTG, &params::TESTGHAT);
+    }
 
-        let _ = data.verify(proof).unwrap();
+    #[test]
+    fn test_ntt_backward() {
+        check_ntt::<8>(false, ntt_params::<F, 8>().testghat, ntt_params::<F, 8>().testg);
+        check_ntt::<64>(false, ntt_params::<F, 64>().testghat, ntt_params::<F, 64>().testg);
+        check_ntt::<{ params::N }>(false, &params::TESTGHAT, &params::TESTG);
     }
 }
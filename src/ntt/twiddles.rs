@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use plonky2::hash::hash_types::RichField;
+use plonky2::util::{log2_strict, reverse_index_bits};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use super::NttParams;
+
+// Runtime counterpart of `gen_param_file.sage`: derives the tables of the negacyclic NTT of
+// ring dimension n (any power of two whose 2n-th roots of unity are in the field) from a
+// primitive 2n-th root of unity psi of the field F, so that ring dimensions without a
+// `params_*.rs` file (and fields other than Goldilocks, which those files are for) can be used.
+// ROOTS and INVROOTS hold the powers of psi and psi^-1 in bit-reversed order, as expected by
+// the butterflies in `ntt_forward` and `ntt_backward`.
+
+fn psi<F: RichField>(logn: usize) -> F {
+    // for Goldilocks this is 7^((q - 1) / 2n), the root used by `gen_param_file.sage`
+    F::primitive_root_of_unity(logn + 1)
+}
+
+fn bit_reversed_powers<F: RichField>(base: F, n: usize) -> Vec<u64> {
+    let powers: Vec<u64> = base.powers().take(n).map(|x| x.to_canonical_u64()).collect();
+    reverse_index_bits(&powers)
+}
+
+// The forward NTT of g, computed naively: the i-th output is g evaluated at the odd power
+// psi^(2 * rev(i) + 1) of psi (with rev(i) the bit reversal of i), i.e. at a root of X^n + 1.
+pub fn naive_ntt<F: RichField>(g: &[F]) -> Vec<F> {
+    let n = g.len();
+    let logn = log2_strict(n);
+    let psi = psi::<F>(logn);
+    let evaluation_points: Vec<F> = (0..n).map(|i| psi.exp_u64(2 * i as u64 + 1)).collect();
+    reverse_index_bits(&evaluation_points)
+        .into_iter()
+        .map(|x| g.iter().rev().fold(F::ZERO, |acc, &gj| acc * x + gj))
+        .collect()
+}
+
+// The NTT tables of a ring dimension computed at run time, which own their elements unlike
+// the `NttParams` of the `params_*.rs` files
+pub struct NttTables {
+    pub logn: u32,
+    pub ninv: u64,
+    pub roots: Vec<u64>,
+    pub invroots: Vec<u64>,
+    pub testg: Vec<u64>,
+    pub testghat: Vec<u64>,
+}
+
+// Computes the NTT tables of ring dimension n over F, with a test vector sampled from a seed
+// of n; use `ntt_params` to compute them only once per ring dimension.
+pub fn compute_ntt_tables<F: RichField>(n: usize) -> NttTables {
+    assert!(
+        n.is_power_of_two() && n >= 2 && log2_strict(n) < F::TWO_ADICITY,
+        "the ring dimension N = {n} should be a power of two between 2 and 2^{}",
+        F::TWO_ADICITY - 1
+    );
+    let logn = log2_strict(n);
+    let psi = psi::<F>(logn);
+
+    let mut rng = ChaCha20Rng::seed_from_u64(n as u64);
+    let testg: Vec<F> = (0..n).map(|_| F::sample(&mut rng)).collect();
+    let testghat = naive_ntt(&testg);
+
+    let canonical = |xs: Vec<F>| xs.into_iter().map(|x| x.to_canonical_u64()).collect();
+    NttTables {
+        logn: logn as u32,
+        ninv: F::inverse_2exp(logn).to_canonical_u64(),
+        roots: bit_reversed_powers(psi, n),
+        invroots: bit_reversed_powers(psi.inverse(), n),
+        testg: canonical(testg),
+        testghat: canonical(testghat),
+    }
+}
+
+// The NTT tables of ring dimension n over F, computed on first use. The cache is keyed by the
+// order of the field and n, and its tables are kept for the life of the program like those of
+// the `params_*.rs` files, so each of them is computed and allocated once.
+pub(super) fn computed_ntt_params<F: RichField>(n: usize) -> &'static NttParams {
+    static COMPUTED: OnceLock<Mutex<HashMap<(u64, usize), &'static NttParams>>> = OnceLock::new();
+    let mut computed = COMPUTED.get_or_init(Default::default).lock().unwrap();
+    computed.entry((F::ORDER, n)).or_insert_with(|| {
+        let tables = compute_ntt_tables::<F>(n);
+        Box::leak(Box::new(NttParams {
+            logn: tables.logn,
+            ninv: tables.ninv,
+            roots: tables.roots.leak(),
+            invroots: tables.invroots.leak(),
+            testg: tables.testg.leak(),
+            testghat: tables.testghat.leak(),
+        }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntt::ntt_params;
+    use crate::vtfhe::crypto::poly::Poly;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+
+    const D: usize = 2;
+    type F = GoldilocksField;
+
+    fn check_tables<const N: usize>() {
+        let expected = ntt_params::<F, N>();
+        let computed = compute_ntt_tables::<F>(N);
+        assert_eq!(computed.logn, expected.logn);
+        assert_eq!(computed.ninv, expected.ninv);
+        assert_eq!(computed.roots, expected.roots);
+        assert_eq!(computed.invroots, expected.invroots);
+
+        let testg: Vec<F> = expected.testg.iter().map(|&x| F::from_canonical_u64(x)).collect();
+        let testghat: Vec<F> = expected.testghat.iter().map(|&x| F::from_canonical_u64(x)).collect();
+        assert_eq!(naive_ntt(&testg), testghat);
+    }
+
+    fn check_ntt<const N: usize>() {
+        let params = ntt_params::<F, N>();
+        let g = Poly::<F, D, N>::from_slice(&params.testg.iter().map(|&x| F::from_canonical_u64(x)).collect::<Vec<_>>());
+        let ghat = Poly::<F, D, N>::from_slice(&params.testghat.iter().map(|&x| F::from_canonical_u64(x)).collect::<Vec<_>>());
+        assert_eq!(g.ntt_fw(), ghat);
+        assert_eq!(ghat.ntt_bw(), g);
+    }
+
+    #[test]
+    fn test_computed_params() {
+        // the tables agree with those generated by `gen_param_file.sage`
+        check_tables::<8>();
+        check_tables::<16>();
+        check_tables::<32>();
+        check_tables::<64>();
+        check_tables::<128>();
+        check_tables::<256>();
+        check_tables::<512>();
+        check_tables::<1024>();
+        check_tables::<2048>();
+
+        // and ring dimensions without a `params_*.rs` file can be used
+        check_ntt::<4>();
+        check_ntt::<4096>();
+        // which are computed only once
+        assert!(std::ptr::eq(ntt_params::<F, 4096>(), ntt_params::<F, 4096>()));
+    }
+}
@@ -1,6 +1,6 @@
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
-use plonky2::iop::target::Target;
+use plonky2::iop::target::{BoolTarget, Target};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 
 pub fn vec_add<F: RichField + Extendable<D>, const D: usize>(
@@ -26,6 +26,19 @@ pub fn vec_mul<F: RichField + Extendable<D>, const D: usize>(
         .collect()
 }
 
+// element-wise `left` if `control` is true, `right` otherwise
+pub fn vec_select<F: RichField + Extendable<D>, const D: usize>(
+    cb: &mut CircuitBuilder<F, D>,
+    control: BoolTarget,
+    left: &[Target],
+    right: &[Target],
+) -> Vec<Target> {
+    left.iter()
+        .zip(right)
+        .map(|(l, r)| cb.select(control, *l, *r))
+        .collect()
+}
+
 pub fn scalar_mul<F: RichField + Extendable<D>, const D: usize>(
     cb: &mut CircuitBuilder<F, D>,
     left: Target,
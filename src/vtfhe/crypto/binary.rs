@@ -0,0 +1,620 @@
+use std::io::{Read, Write};
+
+use anyhow::{bail, ensure, Context, Result};
+use plonky2::{field::extension::Extendable, hash::hash_types::RichField};
+
+use super::compressed::{CompressedGgsw, Seed};
+use super::parameter_set::{ParameterSet, MAX_ID_LEN};
+use super::{ggsw::Ggsw, glwe::Glwe, poly::Poly};
+
+// A versioned little-endian binary container for keys and ciphertexts, a compact alternative
+// to the nested JSON arrays. A container is a sequence of records, each of which is a header
+// (magic, version, record kind, field order, the id of the parameter set and the PBS parameters,
+// and the number of items),
+// followed by the payload: canonical u64 field elements, or raw bytes for `RecordKind::Bytes`.
+// The payload of `RecordKind::CompressedGgsws` is, for each GGSW, its seed followed by its bodies.
+
+pub const MAGIC: [u8; 4] = *b"VFHE";
+pub const VERSION: u16 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    Poly = 1,
+    Glwe = 2,
+    Ggsw = 3,
+    // a list of GGSWs, e.g. the bootstrapping key
+    Ggsws = 4,
+    // an LWE vector, e.g. a ciphertext (mask and body) or a key
+    Lwe = 5,
+    Bytes = 6,
+    // a list of seed-compressed GGSWs, e.g. a compressed bootstrapping key
+    CompressedGgsws = 7,
+}
+
+impl TryFrom<u8> for RecordKind {
+    type Error = anyhow::Error;
+
+    fn try_from(kind: u8) -> Result<Self> {
+        Ok(match kind {
+            1 => RecordKind::Poly,
+            2 => RecordKind::Glwe,
+            3 => RecordKind::Ggsw,
+            4 => RecordKind::Ggsws,
+            5 => RecordKind::Lwe,
+            6 => RecordKind::Bytes,
+            7 => RecordKind::CompressedGgsws,
+            _ => bail!("unknown record kind {kind}"),
+        })
+    }
+}
+
+// the parameters of the PBS a container was written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub lwe_dim: usize,   // n
+    pub ring_dim: usize,  // N
+    pub glwe_dim: usize,  // K
+    pub num_limbs: usize, // ELL
+    pub log_base: usize,  // LOGB
+}
+
+impl From<&ParameterSet> for Params {
+    fn from(params: &ParameterSet) -> Self {
+        Params {
+            lwe_dim: params.lwe_dim,
+            ring_dim: params.ring_dim,
+            glwe_dim: params.glwe_dim,
+            num_limbs: params.num_limbs,
+            log_base: params.log_base,
+        }
+    }
+}
+
+// the id of a parameter set, padded with zeros
+fn padded_id(id: &str) -> [u8; MAX_ID_LEN] {
+    assert!(id.len() <= MAX_ID_LEN, "the id of the parameter set {id} is too long");
+    let mut bytes = [0u8; MAX_ID_LEN];
+    bytes[..id.len()].copy_from_slice(id.as_bytes());
+    bytes
+}
+
+fn unpadded_id(bytes: &[u8; MAX_ID_LEN]) -> String {
+    String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub version: u16,
+    pub kind: RecordKind,
+    pub field_order: u64,
+    pub id: [u8; MAX_ID_LEN],
+    pub params: Params,
+    pub len: u64,
+}
+
+impl Header {
+    pub const NUM_BYTES: usize = 44 + MAX_ID_LEN;
+
+    fn write<W: Write>(&self, w: &mut W) -> Result<()> {
+        w.write_all(&MAGIC)?;
+        w.write_all(&self.version.to_le_bytes())?;
+        w.write_all(&[self.kind as u8, 0])?;
+        w.write_all(&self.field_order.to_le_bytes())?;
+        w.write_all(&self.id)?;
+        for x in [
+            self.params.lwe_dim,
+            self.params.ring_dim,
+            self.params.glwe_dim,
+            self.params.num_limbs,
+            self.params.log_base,
+        ] {
+            w.write_all(&(x as u32).to_le_bytes())?;
+        }
+        w.write_all(&self.len.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn read<R: Read>(r: &mut R) -> Result<Self> {
+        let mut bytes = [0u8; Self::NUM_BYTES];
+        r.read_exact(&mut bytes).context("truncated record header")?;
+        ensure!(bytes[..4] == MAGIC, "not a vfhe binary container (bad magic)");
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        ensure!(
+            version == VERSION,
+            "unsupported container version {version} (expected {VERSION})"
+        );
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap()) as usize;
+        let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+        let params_at = 16 + MAX_ID_LEN;
+        Ok(Header {
+            version,
+            kind: RecordKind::try_from(bytes[6])?,
+            field_order: u64_at(8),
+            id: bytes[16..params_at].try_into().unwrap(),
+            params: Params {
+                lwe_dim: u32_at(params_at),
+                ring_dim: u32_at(params_at + 4),
+                glwe_dim: u32_at(params_at + 8),
+                num_limbs: u32_at(params_at + 12),
+                log_base: u32_at(params_at + 16),
+            },
+            len: u64_at(params_at + 20),
+        })
+    }
+}
+
+pub struct BinaryWriter<W: Write> {
+    w: W,
+    id: [u8; MAX_ID_LEN],
+    params: Params,
+}
+
+impl<W: Write> BinaryWriter<W> {
+    pub fn new(w: W, params: &ParameterSet) -> Self {
+        BinaryWriter {
+            w,
+            id: padded_id(params.id),
+            params: params.into(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+
+    fn write_header(&mut self, kind: RecordKind, field_order: u64, len: usize) -> Result<()> {
+        Header {
+            version: VERSION,
+            kind,
+            field_order,
+            id: self.id,
+            params: self.params,
+            len: len as u64,
+        }
+        .write(&mut self.w)
+    }
+
+    fn write_raw_elements<F: RichField>(&mut self, elements: impl IntoIterator<Item = F>) -> Result<()> {
+        for x in elements {
+            self.w.write_all(&x.to_canonical_u64().to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn write_elements<F: RichField>(
+        &mut self,
+        kind: RecordKind,
+        len: usize,
+        elements: impl IntoIterator<Item = F>,
+    ) -> Result<()> {
+        self.write_header(kind, F::ORDER, len)?;
+        self.write_raw_elements(elements)
+    }
+
+    pub fn write_poly<F: RichField + Extendable<D>, const D: usize, const N: usize>(
+        &mut self,
+        poly: &Poly<F, D, N>,
+    ) -> Result<()> {
+        ensure!(N == self.params.ring_dim, "polynomial of degree {N} does not match the parameters");
+        self.write_elements(RecordKind::Poly, 1, poly.coeffs)
+    }
+
+    pub fn write_glwe<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>(
+        &mut self,
+        glwe: &Glwe<F, D, N, K>,
+    ) -> Result<()> {
+        ensure!(
+            (N, K) == (self.params.ring_dim, self.params.glwe_dim),
+            "GLWE ciphertext with N={N}, K={K} does not match the parameters"
+        );
+        self.write_elements(RecordKind::Glwe, 1, glwe.flatten())
+    }
+
+    pub fn write_ggsw<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    >(
+        &mut self,
+        ggsw: &Ggsw<F, D, N, K, ELL>,
+    ) -> Result<()> {
+        self.write_ggsws(RecordKind::Ggsw, std::slice::from_ref(ggsw))
+    }
+
+    pub fn write_bsk<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    >(
+        &mut self,
+        bsk: &[Ggsw<F, D, N, K, ELL>],
+    ) -> Result<()> {
+        ensure!(bsk.len() == self.params.lwe_dim, "BSK of length {} does not match the parameters", bsk.len());
+        self.write_ggsws(RecordKind::Ggsws, bsk)
+    }
+
+    pub fn write_compressed_bsk<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    >(
+        &mut self,
+        bsk: &[CompressedGgsw<F, D, N, K, ELL>],
+    ) -> Result<()> {
+        ensure!(bsk.len() == self.params.lwe_dim, "BSK of length {} does not match the parameters", bsk.len());
+        ensure!(
+            (N, K, ELL) == (self.params.ring_dim, self.params.glwe_dim, self.params.num_limbs),
+            "GGSW ciphertext with N={N}, K={K}, ELL={ELL} does not match the parameters"
+        );
+        self.write_header(RecordKind::CompressedGgsws, F::ORDER, bsk.len())?;
+        for ggsw in bsk {
+            self.w.write_all(&ggsw.seed)?;
+            self.write_raw_elements(ggsw.bodies.iter().flatten().flat_map(|body| body.coeffs))?;
+        }
+        Ok(())
+    }
+
+    fn write_ggsws<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    >(
+        &mut self,
+        kind: RecordKind,
+        ggsws: &[Ggsw<F, D, N, K, ELL>],
+    ) -> Result<()> {
+        ensure!(
+            (N, K, ELL) == (self.params.ring_dim, self.params.glwe_dim, self.params.num_limbs),
+            "GGSW ciphertext with N={N}, K={K}, ELL={ELL} does not match the parameters"
+        );
+        let elements = ggsws.iter().flat_map(|ggsw| ggsw.flatten());
+        self.write_elements(kind, ggsws.len(), elements)
+    }
+
+    pub fn write_lwe<F: RichField>(&mut self, lwe: &[F]) -> Result<()> {
+        self.write_elements(RecordKind::Lwe, lwe.len(), lwe.iter().copied())
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.write_header(RecordKind::Bytes, 0, bytes.len())?;
+        self.w.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+fn parse_elements<F: RichField>(bytes: &[u8], kind: RecordKind) -> Result<Vec<F>> {
+    bytes
+        .chunks_exact(8)
+        .map(|chunk| {
+            let x = u64::from_le_bytes(chunk.try_into().unwrap());
+            ensure!(x < F::ORDER, "non-canonical field element {x} in {kind:?} record");
+            Ok(F::from_canonical_u64(x))
+        })
+        .collect()
+}
+
+pub struct BinaryReader<R: Read> {
+    r: R,
+    id: [u8; MAX_ID_LEN],
+    params: Params,
+    // the header of the next record, if it was read by `peek_kind`
+    peeked: Option<Header>,
+}
+
+impl<R: Read> BinaryReader<R> {
+    // `params` is the parameter set the records are expected to be written for
+    pub fn new(r: R, params: &ParameterSet) -> Self {
+        BinaryReader {
+            r,
+            id: padded_id(params.id),
+            params: params.into(),
+            peeked: None,
+        }
+    }
+
+    // the kind of the next record, e.g. to tell a compressed bootstrapping key from a plain one
+    pub fn peek_kind(&mut self) -> Result<RecordKind> {
+        if self.peeked.is_none() {
+            self.peeked = Some(Header::read(&mut self.r)?);
+        }
+        Ok(self.peeked.unwrap().kind)
+    }
+
+    fn read_header(&mut self, kind: RecordKind) -> Result<Header> {
+        let header = match self.peeked.take() {
+            Some(header) => header,
+            None => Header::read(&mut self.r)?,
+        };
+        ensure!(
+            header.id == self.id,
+            "record written for the parameter set {}, expected {}",
+            unpadded_id(&header.id),
+            unpadded_id(&self.id)
+        );
+        ensure!(
+            header.kind == kind,
+            "expected a {kind:?} record, found a {:?} record",
+            header.kind
+        );
+        ensure!(
+            header.params == self.params,
+            "record written for parameters {:?}, expected {:?}",
+            header.params,
+            self.params
+        );
+        Ok(header)
+    }
+
+    // reads a record of `len` items of `item_len` field elements each, where `len` is checked
+    // against `expected_len` before anything is allocated
+    fn read_elements<F: RichField>(
+        &mut self,
+        kind: RecordKind,
+        expected_len: usize,
+        item_len: usize,
+    ) -> Result<(usize, Vec<F>)> {
+        let (len, bytes) = self.read_payload::<F>(kind, expected_len, 8 * item_len)?;
+        Ok((len, parse_elements(&bytes, kind)?))
+    }
+
+    // reads the payload of a record of field elements, of `len` items of `item_bytes` bytes each
+    fn read_payload<F: RichField>(
+        &mut self,
+        kind: RecordKind,
+        expected_len: usize,
+        item_bytes: usize,
+    ) -> Result<(usize, Vec<u8>)> {
+        let header = self.read_header(kind)?;
+        ensure!(
+            header.field_order == F::ORDER,
+            "record over the field of order {}, expected {}",
+            header.field_order,
+            F::ORDER
+        );
+        let len = header.len as usize;
+        ensure!(
+            header.len == expected_len as u64,
+            "expected {expected_len} items in {kind:?} record, found {}",
+            header.len
+        );
+        let num_bytes = len
+            .checked_mul(item_bytes)
+            .with_context(|| format!("{kind:?} record of length {len} is too large"))?;
+        let mut bytes = vec![0u8; num_bytes];
+        self.r
+            .read_exact(&mut bytes)
+            .with_context(|| format!("truncated {kind:?} record"))?;
+        Ok((len, bytes))
+    }
+
+    pub fn read_poly<F: RichField + Extendable<D>, const D: usize, const N: usize>(
+        &mut self,
+    ) -> Result<Poly<F, D, N>> {
+        let (_, coeffs) = self.read_elements::<F>(RecordKind::Poly, 1, N)?;
+        Ok(Poly::from_slice(&coeffs))
+    }
+
+    pub fn read_glwe<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>(
+        &mut self,
+    ) -> Result<Glwe<F, D, N, K>> {
+        let (_, elements) = self.read_elements::<F>(RecordKind::Glwe, 1, K * N)?;
+        Ok(Glwe::from_slice(&elements))
+    }
+
+    pub fn read_ggsw<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    >(
+        &mut self,
+    ) -> Result<Ggsw<F, D, N, K, ELL>> {
+        let mut ggsws = self.read_ggsws(RecordKind::Ggsw, 1)?;
+        Ok(ggsws.remove(0))
+    }
+
+    pub fn read_bsk<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    >(
+        &mut self,
+    ) -> Result<Vec<Ggsw<F, D, N, K, ELL>>> {
+        self.read_ggsws(RecordKind::Ggsws, self.params.lwe_dim)
+    }
+
+    pub fn read_compressed_bsk<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    >(
+        &mut self,
+    ) -> Result<Vec<CompressedGgsw<F, D, N, K, ELL>>> {
+        let kind = RecordKind::CompressedGgsws;
+        let seed_len = size_of::<Seed>();
+        let ggsw_bytes = seed_len + 8 * K * ELL * N;
+        let (_, bytes) = self.read_payload::<F>(kind, self.params.lwe_dim, ggsw_bytes)?;
+        bytes
+            .chunks_exact(ggsw_bytes)
+            .map(|chunk| {
+                let bodies: Vec<Poly<F, D, N>> = parse_elements::<F>(&chunk[seed_len..], kind)?
+                    .chunks_exact(N)
+                    .map(Poly::from_slice)
+                    .collect();
+                let mut bodies = bodies.into_iter();
+                Ok(CompressedGgsw {
+                    seed: chunk[..seed_len].try_into().unwrap(),
+                    bodies: std::array::from_fn(|_| std::array::from_fn(|_| bodies.next().unwrap())),
+                })
+            })
+            .collect()
+    }
+
+    fn read_ggsws<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    >(
+        &mut self,
+        kind: RecordKind,
+        len: usize,
+    ) -> Result<Vec<Ggsw<F, D, N, K, ELL>>> {
+        let ggsw_len = K * ELL * K * N;
+        let (_, elements) = self.read_elements::<F>(kind, len, ggsw_len)?;
+        Ok(elements.chunks_exact(ggsw_len).map(Ggsw::from_slice).collect())
+    }
+
+    pub fn read_lwe<F: RichField>(&mut self) -> Result<Vec<F>> {
+        let (_, lwe) = self.read_elements::<F>(RecordKind::Lwe, self.params.lwe_dim + 1, 1)?;
+        Ok(lwe)
+    }
+
+    // The length of a Bytes record (a LUT name or circuit data) is not known in advance, so the
+    // buffer grows with the bytes actually read rather than being allocated from the header.
+    pub fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        let header = self.read_header(RecordKind::Bytes)?;
+        let mut bytes = Vec::new();
+        (&mut self.r)
+            .take(header.len)
+            .read_to_end(&mut bytes)
+            .context("failed to read Bytes record")?;
+        ensure!(bytes.len() as u64 == header.len, "truncated Bytes record");
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::keys::{BootstrapKey, GlweSecretKey, LweSecretKey};
+    use crate::vtfhe::crypto::parameter_set::TEST_TINY;
+    use crate::vtfhe::crypto::rng::test_rng;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Sample;
+
+    #[test]
+    fn test_binary_roundtrip() {
+        const LOGB: usize = 8;
+        const ELL: usize = 8;
+        const K: usize = 2;
+        const D: usize = 2;
+        const n: usize = 2;
+        // a small ring keeps the GGSWs, which are compared by value, off the limits of the stack
+        const N: usize = 8;
+        type F = GoldilocksField;
+        let params = ParameterSet {
+            lwe_dim: n,
+            ring_dim: N,
+            glwe_dim: K,
+            num_limbs: ELL,
+            log_base: LOGB,
+            ..TEST_TINY
+        };
+
+        let rng = &mut test_rng();
+        let s_lwe = LweSecretKey::<F, D, n>::generate(rng);
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, 0f64, rng).into_ggsws();
+        let poly = Poly::<F, D, N>::rand(rng);
+        let glwe = s_glwe.encrypt(&poly, 0f64, rng);
+        let lwe: Vec<F> = F::rand_vec(n + 1);
+
+        let mut writer = BinaryWriter::new(Vec::new(), &params);
+        writer.write_poly(&poly).unwrap();
+        writer.write_glwe(&glwe).unwrap();
+        writer.write_ggsw(&bsk[0]).unwrap();
+        writer.write_bsk(&bsk).unwrap();
+        writer.write_lwe(&lwe).unwrap();
+        writer.write_bytes(b"identity").unwrap();
+        let bytes = writer.into_inner();
+        assert_eq!(
+            bytes.len(),
+            6 * Header::NUM_BYTES + 8 * (N + K * N + (n + 1) * K * ELL * K * N + n + 1) + 8
+        );
+
+        let mut reader = BinaryReader::new(bytes.as_slice(), &params);
+        assert_eq!(poly, reader.read_poly().unwrap());
+        assert_eq!(glwe, reader.read_glwe().unwrap());
+        assert_eq!(bsk[0], reader.read_ggsw().unwrap());
+        assert_eq!(bsk, reader.read_bsk::<F, D, N, K, ELL>().unwrap());
+        assert_eq!(lwe, reader.read_lwe::<F>().unwrap());
+        assert_eq!(b"identity".to_vec(), reader.read_bytes().unwrap());
+
+        // the records are checked against the expected parameters and kinds
+        let other_params = ParameterSet { log_base: 5, ..params };
+        let mut reader = BinaryReader::new(bytes.as_slice(), &other_params);
+        assert!(reader.read_poly::<F, D, N>().is_err());
+        let other_set = ParameterSet { id: "PARAM_128_BIT_P2", ..params };
+        let mut reader = BinaryReader::new(bytes.as_slice(), &other_set);
+        let err = reader.read_poly::<F, D, N>().unwrap_err().to_string();
+        assert_eq!(err, "record written for the parameter set TEST_TINY, expected PARAM_128_BIT_P2");
+        let mut reader = BinaryReader::new(bytes.as_slice(), &params);
+        assert!(reader.read_glwe::<F, D, N, K>().is_err());
+
+        // so is the length of an LWE ciphertext
+        let mut writer = BinaryWriter::new(Vec::new(), &params);
+        writer.write_lwe(&lwe[..n]).unwrap();
+        let bytes = writer.into_inner();
+        let mut reader = BinaryReader::new(bytes.as_slice(), &params);
+        let err = reader.read_lwe::<F>().unwrap_err().to_string();
+        assert_eq!(err, "expected 3 items in Lwe record, found 2");
+
+        // a Bytes record longer than the file is reported as truncated, whatever its length
+        let mut writer = BinaryWriter::new(Vec::new(), &params);
+        writer.write_bytes(b"identity").unwrap();
+        let mut bytes = writer.into_inner();
+        bytes[Header::NUM_BYTES - 8..Header::NUM_BYTES].copy_from_slice(&u64::MAX.to_le_bytes());
+        let mut reader = BinaryReader::new(bytes.as_slice(), &params);
+        let err = reader.read_bytes().unwrap_err().to_string();
+        assert_eq!(err, "truncated Bytes record");
+    }
+
+    #[test]
+    fn test_compressed_bsk_record() {
+        const LOGB: usize = 5;
+        const ELL: usize = 4;
+        const K: usize = 2;
+        const D: usize = 2;
+        const n: usize = 2;
+        type F = GoldilocksField;
+        let params = ParameterSet { lwe_dim: n, ..TEST_TINY };
+
+        let rng = &mut test_rng();
+        let s_lwe = LweSecretKey::<F, D, n>::generate(rng);
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let compressed =
+            BootstrapKey::<F, D, n, N, K, ELL>::generate_compressed::<LOGB>(&s_lwe, &s_glwe, 0f64, rng);
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::decompress(&compressed).into_ggsws();
+
+        let mut writer = BinaryWriter::new(Vec::new(), &params);
+        writer.write_compressed_bsk(&compressed).unwrap();
+        writer.write_bsk(&bsk).unwrap();
+        let bytes = writer.into_inner();
+        // the seeds replace the K - 1 masks of each GLWE ciphertext
+        let compressed_len = Header::NUM_BYTES + n * (32 + 8 * K * ELL * N);
+        assert_eq!(bytes.len(), compressed_len + Header::NUM_BYTES + 8 * n * K * ELL * K * N);
+
+        // a reader can tell which of the two it is reading
+        let mut reader = BinaryReader::new(bytes.as_slice(), &params);
+        assert_eq!(reader.peek_kind().unwrap(), RecordKind::CompressedGgsws);
+        assert_eq!(compressed, reader.read_compressed_bsk::<F, D, N, K, ELL>().unwrap());
+        assert_eq!(reader.peek_kind().unwrap(), RecordKind::Ggsws);
+        assert_eq!(bsk, reader.read_bsk::<F, D, N, K, ELL>().unwrap());
+    }
+}
@@ -0,0 +1,160 @@
+use std::array::from_fn;
+
+use plonky2::{field::extension::Extendable, hash::hash_types::RichField};
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use super::{ggsw::Ggsw, glev::Glev, glwe::Glwe, poly::Poly};
+
+// Seed-compressed ciphertexts: the masks of a ciphertext are uniformly random, so instead of
+// storing them we store the seed of a ChaCha20 RNG they are sampled from, and regenerate them
+// when decompressing. Only the bodies (which depend on the key, the message and the noise) are
+// stored, so a compressed ciphertext is K times smaller. The noise is sampled from the RNG
+// passed to `encrypt`, not from the seed, which is public.
+
+pub type Seed = <ChaCha20Rng as SeedableRng>::Seed;
+
+#[derive(Debug, PartialEq)]
+pub struct CompressedGlwe<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize> {
+    pub seed: Seed,
+    pub body: Poly<F, D, N>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>
+    CompressedGlwe<F, D, N, K>
+{
+    pub fn encrypt(
+        s: &[Poly<F, D, N>],
+        m: &Poly<F, D, N>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let seed: Seed = rng.gen();
+        let mask = Glwe::<F, D, N, K>::sample_mask(&mut ChaCha20Rng::from_seed(seed));
+        let glwe = Glwe::<F, D, N, K>::encrypt_with_mask(s, m, mask, sigma, rng);
+        CompressedGlwe {
+            seed,
+            body: glwe.polys[K - 1].clone(),
+        }
+    }
+
+    pub fn decompress(&self) -> Glwe<F, D, N, K> {
+        let mut mask = Glwe::<F, D, N, K>::sample_mask(&mut ChaCha20Rng::from_seed(self.seed));
+        mask.push(self.body.clone());
+        Glwe {
+            polys: mask.try_into().unwrap(),
+        }
+    }
+}
+
+// A GGSW ciphertext with a single seed for the masks of its K * ELL GLWE ciphertexts, which
+// are sampled in order (GLev by GLev)
+#[derive(Debug, PartialEq)]
+pub struct CompressedGgsw<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+> {
+    pub seed: Seed,
+    pub bodies: [[Poly<F, D, N>; ELL]; K],
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > CompressedGgsw<F, D, N, K, ELL>
+{
+    // the compressed counterpart of `Ggsw::encrypt`
+    pub fn encrypt<const LOGB: usize>(
+        s: &[Poly<F, D, N>],
+        m: &Poly<F, D, N>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let seed: Seed = rng.gen();
+        let mask_rng = &mut ChaCha20Rng::from_seed(seed);
+        let bodies = Ggsw::<F, D, N, K, ELL>::messages(s, m).map(|m_i| {
+            Glev::<F, D, N, K, ELL>::messages::<LOGB>(&m_i).map(|m_ij| {
+                let mask = Glwe::<F, D, N, K>::sample_mask(mask_rng);
+                let glwe = Glwe::<F, D, N, K>::encrypt_with_mask(s, &m_ij, mask, sigma, rng);
+                glwe.polys[K - 1].clone()
+            })
+        });
+        CompressedGgsw { seed, bodies }
+    }
+
+    // the GGSW in coefficient form, as returned by `Ggsw::encrypt`
+    pub fn decompress(&self) -> Ggsw<F, D, N, K, ELL> {
+        let mask_rng = &mut ChaCha20Rng::from_seed(self.seed);
+        Ggsw {
+            glevs: from_fn(|i| Glev {
+                glwes: from_fn(|j| {
+                    let mut mask = Glwe::<F, D, N, K>::sample_mask(mask_rng);
+                    mask.push(self.bodies[i][j].clone());
+                    Glwe {
+                        polys: mask.try_into().unwrap(),
+                    }
+                }),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::keys::{BootstrapKey, GlweSecretKey, LweSecretKey};
+    use crate::vtfhe::crypto::rng::test_rng;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    const LOGB: usize = 5;
+    const ELL: usize = 4;
+    const K: usize = 2;
+    const D: usize = 2;
+    const n: usize = 4;
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_compressed_glwe() {
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
+        let m = Poly::<F, D, N>::rand(rng);
+        let compressed = CompressedGlwe::<F, D, N, K>::encrypt(&s, &m, 0f64, rng);
+        let glwe = compressed.decompress();
+        assert_eq!(glwe.polys[K - 1], compressed.body);
+        assert_eq!(glwe.decrypt(&s), m);
+    }
+
+    #[test]
+    fn test_compressed_bsk() {
+        let rng = &mut test_rng();
+        let s_lwe = LweSecretKey::<F, D, n>::generate(rng);
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let compressed =
+            BootstrapKey::<F, D, n, N, K, ELL>::generate_compressed::<LOGB>(&s_lwe, &s_glwe, 0f64, rng);
+
+        // every GLWE ciphertext of the decompressed GGSWs encrypts the message of `Ggsw::encrypt`
+        for (si, ggsw) in s_lwe.elements().iter().zip(&compressed) {
+            let ggsw = ggsw.decompress();
+            for (glev, m_i) in ggsw.glevs.iter().zip(Ggsw::<F, D, N, K, ELL>::messages(s_glwe.polys(), &Poly::constant(si))) {
+                for (glwe, m_ij) in glev.glwes.iter().zip(Glev::<F, D, N, K, ELL>::messages::<LOGB>(&m_i)) {
+                    assert_eq!(s_glwe.decrypt(glwe), m_ij);
+                }
+            }
+            // and the masks differ between GLWE ciphertexts
+            assert_ne!(ggsw.glevs[0].glwes[0].polys[0], ggsw.glevs[0].glwes[1].polys[0]);
+        }
+        assert_ne!(compressed[0].seed, compressed[1].seed);
+        assert_eq!(compressed[0].decompress(), compressed[0].decompress());
+        assert_eq!(
+            BootstrapKey::<F, D, n, N, K, ELL>::decompress(&compressed).ggsws()[0],
+            compressed[0].decompress().ntt_forward()
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use std::array::from_fn;
 
 use plonky2::{field::extension::Extendable, hash::hash_types::RichField};
+use rand::{CryptoRng, RngCore};
 
-use super::{glev::Glev, poly::Poly};
+use super::{glev::Glev, glwe::Glwe, poly::Poly};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Ggsw<
     F: RichField + Extendable<D>,
     const D: usize,
@@ -23,28 +24,44 @@ impl<
         const ELL: usize,
     > Ggsw<F, D, N, K, ELL>
 {
-    pub fn encrypt<const LOGB: usize>(s: &[Poly<F, D, N>], m: &Poly<F, D, N>, sigma: f64) -> Self {
+    pub fn encrypt<const LOGB: usize>(
+        s: &[Poly<F, D, N>],
+        m: &Poly<F, D, N>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
         Ggsw {
-            glevs: from_fn(|i| {
-                if i < K - 1 {
-                    Glev::<F, D, N, K, ELL>::encrypt::<LOGB>(s, &m.mul(&s[i]), sigma)
-                } else {
-                    Glev::<F, D, N, K, ELL>::encrypt::<LOGB>(s, &m, sigma)
-                }
-            }),
+            glevs: Ggsw::<F, D, N, K, ELL>::messages(s, m)
+                .map(|m_i| Glev::<F, D, N, K, ELL>::encrypt::<LOGB>(s, &m_i, sigma, rng)),
         }
     }
 
-    pub fn compute_ksk<const LOGB: usize>(s_to: &[Poly<F, D, N>], s_from: &[Poly<F, D, N>], sigma: f64) -> Self {
-        Ggsw {
-            glevs: from_fn(|i| {
-                if i < K - 1 {
-                    Glev::<F, D, N, K, ELL>::encrypt::<LOGB>(s_to, &s_from[i], sigma)
-                } else {
-                    Glev::<F, D, N, K, ELL>::encrypt::<LOGB>(s_to, &Poly::constant(&F::ONE), sigma)
-                }
-            }),
-        }.ntt_forward()
+    // the messages of the K GLevs: m times the K - 1 polynomials of the key, then m
+    pub fn messages(s: &[Poly<F, D, N>], m: &Poly<F, D, N>) -> [Poly<F, D, N>; K] {
+        from_fn(|i| if i < K - 1 { m.mul(&s[i]) } else { m.clone() })
+    }
+
+    // expects the GGSW in NTT form, the GLWE and the output are in coefficient form
+    pub fn external_product<const LOGB: usize>(&self, glwe: &Glwe<F, D, N, K>) -> Glwe<F, D, N, K> {
+        let glev_muls: Vec<Glwe<F, D, N, K>> = glwe
+            .polys
+            .iter()
+            .zip(self.glevs.iter())
+            .map(|(poly, glev)| glev.mul::<LOGB>(poly))
+            .collect();
+        let sum_polys = glev_muls[..K - 1]
+            .iter()
+            .fold(Glwe::dummy_ct(), |acc, x| acc.add(x));
+        glev_muls[K - 1].sub(&sum_polys).ntt_backward()
+    }
+
+    // selects `right` if the GGSW encrypts 1 and `left` if it encrypts 0
+    pub fn cmux<const LOGB: usize>(
+        &self,
+        left: &Glwe<F, D, N, K>,
+        right: &Glwe<F, D, N, K>,
+    ) -> Glwe<F, D, N, K> {
+        self.external_product::<LOGB>(&right.sub(left)).add(left)
     }
 
     pub fn ntt_forward(&self) -> Self {
@@ -62,4 +79,68 @@ impl<
     pub fn flatten(&self) -> Vec<F> {
         self.glevs.iter().flat_map(|glev| glev.flatten()).collect()
     }
+
+    pub fn from_slice(slice: &[F]) -> Self {
+        let glev_len = ELL * K * N;
+        Ggsw {
+            glevs: from_fn(|i| Glev::from_slice(&slice[i * glev_len..(i + 1) * glev_len])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vtfhe::crypto::rng::test_rng;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+    use rand::Rng;
+
+    // a small ring keeps the GGSWs, which are passed by value, off the limits of the stack
+    const N: usize = 8;
+
+    #[test]
+    fn test_external_product() {
+        const LOGB: usize = 8;
+        const ELL: usize = 8;
+        const K: usize = 3;
+        const D: usize = 2;
+        type F = GoldilocksField;
+
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
+        let m_glwe = Poly::<F, D, N>::rand(rng);
+        let bit = F::from_canonical_u64(rng.gen::<u64>() % 2);
+
+        let ct_glwe = Glwe::<F, D, N, K>::encrypt(&s, &m_glwe, 0f64, rng);
+        let ct_ggsw = Ggsw::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &Poly::constant(&bit), 0f64, rng)
+            .ntt_forward();
+
+        let m_out = ct_ggsw.external_product::<LOGB>(&ct_glwe).decrypt(&s);
+        assert_eq!(m_glwe.scalar_mul(&bit), m_out);
+    }
+
+    #[test]
+    fn test_cmux() {
+        const LOGB: usize = 8;
+        const ELL: usize = 8;
+        const K: usize = 2;
+        const D: usize = 2;
+        type F = GoldilocksField;
+
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
+        let m_left = Poly::<F, D, N>::rand(rng);
+        let m_right = Poly::<F, D, N>::rand(rng);
+        let ct_left = Glwe::<F, D, N, K>::encrypt(&s, &m_left, 0f64, rng);
+        let ct_right = Glwe::<F, D, N, K>::encrypt(&s, &m_right, 0f64, rng);
+
+        for (bit, m_expected) in [(F::ZERO, &m_left), (F::ONE, &m_right)] {
+            let ct_ggsw =
+                Ggsw::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &Poly::constant(&bit), 0f64, rng)
+                    .ntt_forward();
+            let m_out = ct_ggsw.cmux::<LOGB>(&ct_left, &ct_right).decrypt(&s);
+            assert_eq!(*m_expected, m_out);
+        }
+    }
 }
@@ -1,10 +1,11 @@
 use std::array::from_fn;
 
 use plonky2::{field::extension::Extendable, hash::hash_types::RichField, util::ceil_div_usize};
+use rand::{CryptoRng, RngCore};
 
 use super::{glwe::Glwe, poly::Poly};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Glev<
     F: RichField + Extendable<D>,
     const D: usize,
@@ -23,16 +24,41 @@ impl<
         const ELL: usize,
     > Glev<F, D, N, K, ELL>
 {
-    pub fn encrypt<const LOGB: usize>(s: &[Poly<F, D, N>], m: &Poly<F, D, N>, sigma: f64) -> Self {
+    pub fn encrypt<const LOGB: usize>(
+        s: &[Poly<F, D, N>],
+        m: &Poly<F, D, N>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        Glev {
+            glwes: Glev::<F, D, N, K, ELL>::messages::<LOGB>(m).map(|m_i| Glwe::encrypt(s, &m_i, sigma, rng)),
+        }
+    }
+
+    // the messages of the ELL GLWE ciphertexts: m times the powers of the base of the ELL
+    // limbs kept by the decomposition
+    pub fn messages<const LOGB: usize>(m: &Poly<F, D, N>) -> [Poly<F, D, N>; ELL] {
         let base = F::TWO.exp_u64(LOGB as u64);
         let first_limb = ceil_div_usize(F::BITS, LOGB) - ELL;
-        Glev {
-            glwes: from_fn(|i| {
-                Glwe::encrypt(
-                    s,
-                    &m.scalar_mul(&base.exp_u64((first_limb + i) as u64)),
-                    sigma,
-                )
+        from_fn(|i| m.scalar_mul(&base.exp_u64((first_limb + i) as u64)))
+    }
+
+    // expects the GLev in NTT form and returns the product in NTT form
+    pub fn mul<const LOGB: usize>(&self, poly: &Poly<F, D, N>) -> Glwe<F, D, N, K> {
+        let num_limbs = ceil_div_usize(F::BITS, LOGB);
+        let limbs = poly.decompose::<LOGB>(num_limbs);
+        let limbs_hat: Vec<Poly<F, D, N>> = limbs[num_limbs - ELL..]
+            .iter()
+            .map(|limb| limb.ntt_fw())
+            .collect();
+        Glwe {
+            polys: from_fn(|index| {
+                limbs_hat
+                    .iter()
+                    .zip(self.glwes.iter())
+                    .map(|(limb_hat, glwe)| limb_hat.pointwise_mul(&glwe.polys[index]))
+                    .reduce(|acc, x| acc.add(&x))
+                    .unwrap()
             }),
         }
     }
@@ -52,4 +78,10 @@ impl<
     pub fn flatten(&self) -> Vec<F> {
         self.glwes.iter().flat_map(|glwe| glwe.flatten()).collect()
     }
+
+    pub fn from_slice(slice: &[F]) -> Self {
+        Glev {
+            glwes: from_fn(|i| Glwe::from_slice(&slice[i * K * N..(i + 1) * K * N])),
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use itertools::max;
 use plonky2::{field::extension::Extendable, hash::hash_types::RichField};
+use rand::{CryptoRng, RngCore};
 use std::array::from_fn;
 
 use super::poly::Poly;
@@ -12,18 +13,18 @@ pub struct Glwe<F: RichField + Extendable<D>, const D: usize, const N: usize, co
 impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>
     Glwe<F, D, N, K>
 {
-    pub fn key_gen() -> Vec<Poly<F, D, N>> {
-        (0..K - 1).map(|_| Poly::rand_bin()).collect()
+    pub fn key_gen(rng: &mut (impl RngCore + CryptoRng)) -> Vec<Poly<F, D, N>> {
+        (0..K - 1).map(|_| Poly::rand_bin(rng)).collect()
     }
 
-    pub fn partial_key(nz: usize) -> Vec<Poly<F, D, N>> {
+    pub fn partial_key(nz: usize, rng: &mut (impl RngCore + CryptoRng)) -> Vec<Poly<F, D, N>> {
         let mut key = Vec::new();
         for _ in 0..(nz / N).min(K) {
-            key.push(Poly::rand_bin());
+            key.push(Poly::rand_bin(rng));
         }
 
         if nz / N < K {
-            let mut poly = Poly::rand_bin();
+            let mut poly = Poly::rand_bin(rng);
             for i in nz % N..N {
                 poly.coeffs[i] = F::ZERO;
             }
@@ -45,10 +46,30 @@ impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usiz
             .unwrap()
     }
 
-    // noiseless
-    pub fn encrypt(s: &[Poly<F, D, N>], m: &Poly<F, D, N>, sigma: f64) -> Self {
-        let mut mask: Vec<Poly<F, D, N>> = (0..K - 1).map(|_| Poly::rand()).collect();
-        let error = Poly::rand_error(sigma);
+    pub fn encrypt(
+        s: &[Poly<F, D, N>],
+        m: &Poly<F, D, N>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let mask = Glwe::<F, D, N, K>::sample_mask(rng);
+        Glwe::<F, D, N, K>::encrypt_with_mask(s, m, mask, sigma, rng)
+    }
+
+    // the K - 1 uniformly random mask polynomials of a ciphertext
+    pub fn sample_mask(rng: &mut (impl RngCore + CryptoRng)) -> Vec<Poly<F, D, N>> {
+        (0..K - 1).map(|_| Poly::rand(rng)).collect()
+    }
+
+    // encryption with a given mask, e.g. one regenerated from a seed (see `compressed`)
+    pub fn encrypt_with_mask(
+        s: &[Poly<F, D, N>],
+        m: &Poly<F, D, N>,
+        mut mask: Vec<Poly<F, D, N>>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let error = Poly::rand_error(sigma, rng);
         let body = Glwe::<F, D, N, K>::poly_inner(s, &mask).add(&error);
         mask.push(body.add(m));
         Glwe {
@@ -62,6 +83,25 @@ impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usiz
         self.polys[K - 1].sub(&mask)
     }
 
+    pub fn add(&self, other: &Glwe<F, D, N, K>) -> Self {
+        Glwe {
+            polys: from_fn(|i| self.polys[i].add(&other.polys[i])),
+        }
+    }
+
+    pub fn sub(&self, other: &Glwe<F, D, N, K>) -> Self {
+        Glwe {
+            polys: from_fn(|i| self.polys[i].sub(&other.polys[i])),
+        }
+    }
+
+    // multiplication by X^shift
+    pub fn rotate(&self, shift: usize) -> Self {
+        Glwe {
+            polys: from_fn(|i| self.polys[i].rotate(shift)),
+        }
+    }
+
     pub fn get_max_error(&self, s: &[Poly<F, D, N>], m: &Poly<F, D, N>) -> f64 {
         let mbar = self.decrypt(s);
         let errors = m
@@ -164,6 +204,7 @@ mod tests {
     use super::*;
     use crate::ntt::params::N;
     use crate::vtfhe::crypto::lwe::decrypt;
+    use crate::vtfhe::crypto::rng::test_rng;
     use plonky2::field::goldilocks_field::GoldilocksField;
 
     #[test]
@@ -173,10 +214,11 @@ mod tests {
         const n: usize = (K - 1) * N;
         type F = GoldilocksField;
 
-        let s = Glwe::<F, D, N, K>::key_gen();
-        let m = Poly::<F, D, N>::rand();
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
+        let m = Poly::<F, D, N>::rand(rng);
 
-        let c = Glwe::<F, D, N, K>::encrypt(&s, &m, 0f64);
+        let c = Glwe::<F, D, N, K>::encrypt(&s, &m, 0f64, rng);
         let m_ = c.decrypt(&s);
         assert_eq!(m, m_);
 
@@ -187,7 +229,7 @@ mod tests {
         let m0 = decrypt::<F, D, n>(&s0, &c0);
         assert_eq!(m0, m.coeffs[0]);
 
-        let c = Glwe::<F, D, N, K>::encrypt(&s, &m, 0.00000004990272175010415);
+        let c = Glwe::<F, D, N, K>::encrypt(&s, &m, 0.00000004990272175010415, rng);
         println!("max error: {}", c.get_max_error(&s, &m));
     }
 
@@ -198,10 +240,11 @@ mod tests {
         const n: usize = (K - 2) * N + N / 2;
         type F = GoldilocksField;
 
-        let s = Glwe::<F, D, N, K>::partial_key(n);
-        let m = Poly::<F, D, N>::rand();
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::partial_key(n, rng);
+        let m = Poly::<F, D, N>::rand(rng);
 
-        let c = Glwe::<F, D, N, K>::encrypt(&s, &m, 0f64);
+        let c = Glwe::<F, D, N, K>::encrypt(&s, &m, 0f64, rng);
         let m_ = c.decrypt(&s);
         assert_eq!(m, m_);
 
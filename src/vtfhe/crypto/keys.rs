@@ -0,0 +1,349 @@
+use std::array::from_fn;
+
+use plonky2::{field::extension::Extendable, hash::hash_types::RichField, util::ceil_div_usize};
+use rand::{CryptoRng, RngCore};
+
+use super::{
+    compressed::CompressedGgsw,
+    ggsw::Ggsw,
+    glev::Glev,
+    glwe::Glwe,
+    lwe,
+    poly::Poly,
+};
+
+// The keys of the PBS, with their dimensions as const generics so that, e.g., an LWE key can't
+// be passed where a GLWE key is expected. The input ciphertext of the PBS is encrypted under an
+// `LweSecretKey`, the `BootstrapKey` encrypts its bits under a `GlweSecretKey`, and the
+// `KeySwitchKey` switches the blind-rotated accumulator back to the output key, a partial GLWE
+// key whose first n coefficients are the LWE key. Alternatively, an `LweKeySwitchKey` switches
+// it to an independent LWE key.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LweSecretKey<F: RichField + Extendable<D>, const D: usize, const n: usize> {
+    elements: Vec<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const n: usize> LweSecretKey<F, D, n> {
+    pub fn generate(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        LweSecretKey {
+            elements: lwe::key_gen::<F, D, n>(rng),
+        }
+    }
+
+    pub fn from_elements(elements: Vec<F>) -> Self {
+        assert_eq!(elements.len(), n, "expected {n} elements in an LWE key, found {}", elements.len());
+        LweSecretKey { elements }
+    }
+
+    pub fn elements(&self) -> &[F] {
+        &self.elements
+    }
+
+    pub fn encrypt(&self, m: &F, sigma: f64, rng: &mut (impl RngCore + CryptoRng)) -> Vec<F> {
+        lwe::encrypt::<F, D, n>(&self.elements, m, sigma, rng)
+    }
+
+    pub fn decrypt(&self, ct: &[F]) -> F {
+        lwe::decrypt::<F, D, n>(&self.elements, ct)
+    }
+}
+
+// the K - 1 polynomials of a GLWE key
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlweSecretKey<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize> {
+    polys: Vec<Poly<F, D, N>>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>
+    GlweSecretKey<F, D, N, K>
+{
+    pub fn generate(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        GlweSecretKey {
+            polys: Glwe::<F, D, N, K>::key_gen(rng),
+        }
+    }
+
+    // a key whose first n coefficients are random bits and the others zero, the output key of
+    // a PBS whose input is encrypted under `to_lwe_key`
+    pub fn generate_partial<const n: usize>(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let mut polys = Glwe::<F, D, N, K>::partial_key(n, rng);
+        polys.truncate(K - 1);
+        GlweSecretKey { polys }
+    }
+
+    pub fn from_polys(polys: Vec<Poly<F, D, N>>) -> Self {
+        assert_eq!(polys.len(), K - 1, "expected {} polynomials in a GLWE key, found {}", K - 1, polys.len());
+        GlweSecretKey { polys }
+    }
+
+    pub fn polys(&self) -> &[Poly<F, D, N>] {
+        &self.polys
+    }
+
+    pub fn into_polys(self) -> Vec<Poly<F, D, N>> {
+        self.polys
+    }
+
+    // the LWE key made of the first n coefficients, see `generate_partial`
+    pub fn to_lwe_key<const n: usize>(&self) -> LweSecretKey<F, D, n> {
+        LweSecretKey::from_elements(Glwe::<F, D, N, K>::flatten_partial_key(&self.polys, n))
+    }
+
+    pub fn encrypt(&self, m: &Poly<F, D, N>, sigma: f64, rng: &mut (impl RngCore + CryptoRng)) -> Glwe<F, D, N, K> {
+        Glwe::encrypt(&self.polys, m, sigma, rng)
+    }
+
+    pub fn decrypt(&self, ct: &Glwe<F, D, N, K>) -> Poly<F, D, N> {
+        ct.decrypt(&self.polys)
+    }
+}
+
+// the n GGSW encryptions (in NTT form) of the bits of an LWE key under a GLWE key, which the
+// blind rotation consumes in order
+#[derive(Debug, PartialEq)]
+pub struct BootstrapKey<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+> {
+    ggsws: Vec<Ggsw<F, D, N, K, ELL>>,
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const n: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > BootstrapKey<F, D, n, N, K, ELL>
+{
+    pub fn generate<const LOGB: usize>(
+        lwe_key: &LweSecretKey<F, D, n>,
+        glwe_key: &GlweSecretKey<F, D, N, K>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        BootstrapKey {
+            ggsws: lwe_key
+                .elements()
+                .iter()
+                .map(|si| Ggsw::encrypt::<LOGB>(glwe_key.polys(), &Poly::constant(si), sigma, rng).ntt_forward())
+                .collect(),
+        }
+    }
+
+    // the seed-compressed counterpart of `generate`, see `compressed`
+    pub fn generate_compressed<const LOGB: usize>(
+        lwe_key: &LweSecretKey<F, D, n>,
+        glwe_key: &GlweSecretKey<F, D, N, K>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Vec<CompressedGgsw<F, D, N, K, ELL>> {
+        lwe_key
+            .elements()
+            .iter()
+            .map(|si| CompressedGgsw::encrypt::<LOGB>(glwe_key.polys(), &Poly::constant(si), sigma, rng))
+            .collect()
+    }
+
+    pub fn decompress(compressed: &[CompressedGgsw<F, D, N, K, ELL>]) -> Self {
+        BootstrapKey::from_ggsws(compressed.iter().map(|ggsw| ggsw.decompress().ntt_forward()).collect())
+    }
+
+    pub fn from_ggsws(ggsws: Vec<Ggsw<F, D, N, K, ELL>>) -> Self {
+        assert_eq!(ggsws.len(), n, "expected {n} GGSWs in a bootstrapping key, found {}", ggsws.len());
+        BootstrapKey { ggsws }
+    }
+
+    pub fn ggsws(&self) -> &[Ggsw<F, D, N, K, ELL>] {
+        &self.ggsws
+    }
+
+    pub fn into_ggsws(self) -> Vec<Ggsw<F, D, N, K, ELL>> {
+        self.ggsws
+    }
+}
+
+// Switches a GLWE ciphertext from one GLWE key to another with an external product: a GGSW (in
+// NTT form) whose first K - 1 GLevs encrypt the polynomials of the old key under the new one and
+// whose last GLev encrypts 1, so that the product with (a, b) is an encryption of b - <a, s>.
+#[derive(Debug, PartialEq)]
+pub struct KeySwitchKey<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+> {
+    ggsw: Ggsw<F, D, N, K, ELL>,
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > KeySwitchKey<F, D, N, K, ELL>
+{
+    pub fn generate<const LOGB: usize>(
+        from: &GlweSecretKey<F, D, N, K>,
+        to: &GlweSecretKey<F, D, N, K>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let one = Poly::constant(&F::ONE);
+        let messages = from.polys().iter().chain([&one]);
+        let glevs: Vec<Glev<F, D, N, K, ELL>> = messages
+            .map(|m| Glev::encrypt::<LOGB>(to.polys(), m, sigma, rng))
+            .collect();
+        KeySwitchKey {
+            ggsw: Ggsw {
+                glevs: glevs.try_into().unwrap(),
+            }
+            .ntt_forward(),
+        }
+    }
+
+    pub fn from_ggsw(ggsw: Ggsw<F, D, N, K, ELL>) -> Self {
+        KeySwitchKey { ggsw }
+    }
+
+    pub fn ggsw(&self) -> &Ggsw<F, D, N, K, ELL> {
+        &self.ggsw
+    }
+
+    pub fn into_ggsw(self) -> Ggsw<F, D, N, K, ELL> {
+        self.ggsw
+    }
+}
+
+// Switches the LWE ciphertext sample-extracted from a GLWE ciphertext to an LWE key of dimension
+// n, which is independent of the GLWE key (unlike the output key of a `KeySwitchKey`), so that
+// the output can be bootstrapped again. It has a Lev (ELL LWE ciphertexts of the message times
+// the powers of B of the top ELL limbs, like a GLev) per coefficient of the mask, which encrypts
+// minus the key element that multiplies the coefficient in the phase of the constant
+// coefficient: s_0 for the constant coefficient and -s_(N - j) for the j-th one (see
+// `Glwe::sample_extract`). The key switch thus reads off the coefficients of the mask and adds
+// their products with the Levs to the body (see `vtfhe::key_switch`).
+#[derive(Debug, PartialEq)]
+pub struct LweKeySwitchKey<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+> {
+    levs: Vec<[Vec<F>; ELL]>,
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const n: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > LweKeySwitchKey<F, D, n, N, K, ELL>
+{
+    // the number of Levs that fit in the elements of a GGSW, i.e. the number of coefficients of
+    // the mask switched by a step of the IVC (see `step_ggsws`)
+    pub const CHUNK_SIZE: usize = {
+        let chunk_size = K * K * N / (n + 1);
+        if chunk_size < (K - 1) * N {
+            chunk_size
+        } else {
+            (K - 1) * N
+        }
+    };
+    pub const NUM_CHUNKS: usize = ((K - 1) * N).div_ceil(Self::CHUNK_SIZE);
+
+    pub fn generate<const LOGB: usize>(
+        from: &GlweSecretKey<F, D, N, K>,
+        to: &LweSecretKey<F, D, n>,
+        sigma: f64,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let base = F::TWO.exp_u64(LOGB as u64);
+        let first_limb = ceil_div_usize(F::BITS, LOGB) - ELL;
+        let key_elements: Vec<F> = from
+            .polys()
+            .iter()
+            .flat_map(|s| (0..N).map(|j| if j == 0 { s.coeffs[0] } else { -s.coeffs[N - j] }))
+            .collect();
+        LweKeySwitchKey {
+            levs: key_elements
+                .iter()
+                .map(|sj| {
+                    from_fn(|i| to.encrypt(&(-*sj * base.exp_u64((first_limb + i) as u64)), sigma, rng))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn levs(&self) -> &[[Vec<F>; ELL]] {
+        &self.levs
+    }
+
+    // The KSK in chunks of `CHUNK_SIZE` Levs, each padded with zeros to the size of a GGSW, so
+    // that the steps of the IVC that switch the key consume them in place of the GGSWs of the BSK
+    pub fn step_ggsws(&self) -> Vec<Ggsw<F, D, N, K, ELL>> {
+        self.levs
+            .chunks(Self::CHUNK_SIZE)
+            .map(|chunk| {
+                let mut elements: Vec<F> = chunk.iter().flatten().flatten().copied().collect();
+                elements.resize(K * K * ELL * N, F::ZERO);
+                Ggsw::from_slice(&elements)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::rng::test_rng;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Sample};
+
+    const LOGB: usize = 8;
+    const ELL: usize = 8;
+    const K: usize = 2;
+    const D: usize = 2;
+    const n: usize = 4;
+    type F = GoldilocksField;
+
+    #[test]
+    fn test_keys() {
+        let rng = &mut test_rng();
+        let s_to = GlweSecretKey::<F, D, N, K>::generate_partial::<n>(rng);
+        let s_lwe = s_to.to_lwe_key::<n>();
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        assert_eq!(s_to.polys().len(), K - 1);
+        assert_eq!(s_lwe.elements(), &s_to.polys()[0].coeffs[..n]);
+        assert!(s_to.polys()[0].coeffs[n..].iter().all(|c| *c == F::ZERO));
+
+        let m = F::sample(rng);
+        assert_eq!(s_lwe.decrypt(&s_lwe.encrypt(&m, 0f64, rng)), m);
+
+        // one GGSW per bit of the LWE key, each encrypting the bit under the GLWE key
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, 0f64, rng);
+        assert_eq!(bsk.ggsws().len(), n);
+        let poly = Poly::<F, D, N>::rand(rng);
+        let glwe = s_glwe.encrypt(&poly, 0f64, rng);
+        for (ggsw, si) in bsk.ggsws().iter().zip(s_lwe.elements()) {
+            assert_eq!(s_glwe.decrypt(&ggsw.external_product::<LOGB>(&glwe)), poly.scalar_mul(si));
+        }
+
+        // the key switch decrypts under the output key
+        let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_to, 0f64, rng);
+        assert_eq!(s_to.decrypt(&ksk.ggsw().external_product::<LOGB>(&glwe)), poly);
+    }
+}
@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error};
+use plonky2::{field::extension::Extendable, hash::hash_types::RichField};
+
+use super::{get_testv_lut, poly::Poly};
+
+// Named lookup tables f: Z_p -> Z_p that can be evaluated by a programmable bootstrap.
+// The name is all a verifier needs to recompute the test vector bound by the proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lut {
+    Identity,
+    Not,
+    Negate,
+    Double,
+    Square,
+}
+
+impl Lut {
+    pub const ALL: [Lut; 5] = [Lut::Identity, Lut::Not, Lut::Negate, Lut::Double, Lut::Square];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lut::Identity => "identity",
+            Lut::Not => "not",
+            Lut::Negate => "negate",
+            Lut::Double => "double",
+            Lut::Square => "square",
+        }
+    }
+
+    pub fn eval(&self, m: usize, p: usize) -> usize {
+        match self {
+            Lut::Identity => m % p,
+            Lut::Not => p - 1 - m % p,
+            Lut::Negate => (p - m % p) % p,
+            Lut::Double => (2 * m) % p,
+            Lut::Square => (m * m) % p,
+        }
+    }
+
+    pub fn testv<F: RichField + Extendable<D>, const D: usize, const N: usize>(
+        &self,
+        p: usize,
+        delta: F,
+    ) -> Poly<F, D, N> {
+        get_testv_lut(p, delta, |m| self.eval(m, p))
+    }
+}
+
+impl FromStr for Lut {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lut::ALL
+            .into_iter()
+            .find(|lut| lut.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Lut::ALL.iter().map(|lut| lut.name()).collect();
+                anyhow!("unknown LUT '{s}', expected one of: {}", names.join(", "))
+            })
+    }
+}
@@ -1,4 +1,5 @@
 use plonky2::{field::extension::Extendable, hash::hash_types::RichField, util::log2_ceil};
+use rand::{CryptoRng, Rng, RngCore};
 use rand_distr::{Distribution, Normal};
 
 pub fn inner_product<F: RichField + Extendable<D>, const D: usize>(left: &[F], right: &[F]) -> F {
@@ -7,9 +8,11 @@ pub fn inner_product<F: RichField + Extendable<D>, const D: usize>(left: &[F], r
         .fold(F::ZERO, |acc, (li, ri)| acc + (*li * *ri))
 }
 
-pub fn key_gen<F: RichField + Extendable<D>, const D: usize, const n: usize>() -> Vec<F> {
+pub fn key_gen<F: RichField + Extendable<D>, const D: usize, const n: usize>(
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Vec<F> {
     (0..n)
-        .map(|_| F::from_canonical_u64(rand::random::<u64>() % 2))
+        .map(|_| F::from_canonical_u64(rng.gen::<u64>() % 2))
         .collect()
 }
 
@@ -37,10 +40,13 @@ pub fn mod_switch_ct<F: RichField + Extendable<D>, const D: usize>(ct: &[F], p:
     ct.iter().map(|element| mod_switch_element(*element, p)).collect()
 }
 
-pub fn error_sample<F: RichField + Extendable<D>, const D: usize>(sigma: f64) -> F {
+pub fn error_sample<F: RichField + Extendable<D>, const D: usize>(
+    sigma: f64,
+    rng: &mut (impl RngCore + CryptoRng),
+) -> F {
     let q = F::ORDER as f64;
     let normal = Normal::new(0.0, sigma * q).unwrap();
-    F::from_noncanonical_i64(normal.sample(&mut rand::thread_rng()).round() as i64)
+    F::from_noncanonical_i64(normal.sample(rng).round() as i64)
 }
 
 pub fn get_delta<F: RichField + Extendable<D>, const D: usize>(p: usize) -> F {
@@ -51,9 +57,10 @@ pub fn encrypt<F: RichField + Extendable<D>, const D: usize, const n: usize>(
     s: &[F],
     m: &F,
     sigma: f64,
+    rng: &mut (impl RngCore + CryptoRng),
 ) -> Vec<F> {
-    let mut mask: Vec<F> = (0..n).map(|_| F::rand()).collect();
-    let body = inner_product(s, &mask) + *m + error_sample(sigma);
+    let mut mask: Vec<F> = (0..n).map(|_| F::sample(rng)).collect();
+    let body = inner_product(s, &mask) + *m + error_sample(sigma, rng);
     mask.push(body);
     mask
 }
@@ -71,9 +78,10 @@ pub fn decrypt<F: RichField + Extendable<D>, const D: usize, const n: usize>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::vtfhe::crypto::rng::test_rng;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::field::types::{Field, PrimeField64};
-    use rand::random;
+    use rand::Rng;
 
     #[test]
     fn test_lwe_ct() {
@@ -84,12 +92,13 @@ mod tests {
         let p = 4;
         let delta = get_delta::<F, D>(p);
 
-        let s = key_gen::<F, D, n>();
-        let m1 = F::from_canonical_usize(random::<usize>() % p);
-        let m2 = F::from_canonical_usize(random::<usize>() % p);
+        let rng = &mut test_rng();
+        let s = key_gen::<F, D, n>(rng);
+        let m1 = F::from_canonical_usize(rng.gen::<usize>() % p);
+        let m2 = F::from_canonical_usize(rng.gen::<usize>() % p);
 
-        let c1 = encrypt::<F, D, n>(&s, &(delta * m1), sigma);
-        let c2 = encrypt::<F, D, n>(&s, &(delta * m2), sigma);
+        let c1 = encrypt::<F, D, n>(&s, &(delta * m1), sigma, rng);
+        let c2 = encrypt::<F, D, n>(&s, &(delta * m2), sigma, rng);
 
         println!("error 1: {}", get_error::<F, D, n>(&c1, &s, delta * m1));
         println!("error 2: {}", get_error::<F, D, n>(&c2, &s, delta * m2));
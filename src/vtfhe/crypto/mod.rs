@@ -1,45 +1,324 @@
 /*
     This submodule contains the structures that hold the data for the circuits. They
     can be used to compute and set the inputs and retrieve the outputs of the
-    verifiable circuits.
+    verifiable circuits. The native operations below mirror the circuit gadgets in
+    the parent module bit for bit, so they serve as a reference for their outputs.
 */
 
-use plonky2::{field::extension::Extendable, hash::hash_types::RichField};
+use plonky2::{field::extension::Extendable, hash::hash_types::RichField, util::ceil_div_usize};
 
-use self::{ggsw::Ggsw, poly::Poly};
+use self::{
+    ggsw::Ggsw,
+    glwe::Glwe,
+    keys::LweKeySwitchKey,
+    lwe::mod_switch_element,
+    poly::{decompose, Poly},
+};
 
+pub mod binary;
+pub mod compressed;
 pub mod ggsw;
 pub mod glev;
 pub mod glwe;
+pub mod keys;
+pub mod lut;
 pub mod lwe;
+pub mod noise;
+pub mod parameter_set;
 pub mod poly;
+pub mod rng;
+pub mod serialization;
 
 pub fn get_testv<F: RichField + Extendable<D>, const D: usize, const N: usize>(
     p: usize,
     delta: F,
+) -> Poly<F, D, N> {
+    get_testv_lut(p, delta, |m| m)
+}
+
+// test vector for a PBS that evaluates f: Z_p -> Z_p on the message; the half-block shift
+// makes messages with small negative noise wrap around negacyclically to the block of f(0)
+pub fn get_testv_lut<F: RichField + Extendable<D>, const D: usize, const N: usize>(
+    p: usize,
+    delta: F,
+    f: impl Fn(usize) -> usize,
 ) -> Poly<F, D, N> {
     let block_size = N / p;
     let coeffs: Vec<F> = (0..p)
-        .flat_map(|i| vec![F::from_canonical_usize(i) * delta; block_size])
+        .flat_map(|i| vec![F::from_canonical_usize(f(i) % p) * delta; block_size])
         .collect();
 
     Poly::from_slice(&coeffs).left_shift(block_size / 2)
 }
 
-pub fn compute_bsk<
+// native counterpart of `vtfhe::rotate_glwe`
+pub fn rotate_glwe<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>(
+    glwe: &Glwe<F, D, N, K>,
+    shift: F,
+) -> Glwe<F, D, N, K> {
+    glwe.rotate(mod_switch_element(shift, N))
+}
+
+// native counterpart of `vtfhe::blind_rotation_step`
+pub fn blind_rotation_step<
     F: RichField + Extendable<D>,
     const D: usize,
+    const LOGB: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+>(
+    glwe: &Glwe<F, D, N, K>,
+    ggsw: &Ggsw<F, D, N, K, ELL>,
+    mask_element: F,
+) -> Glwe<F, D, N, K> {
+    ggsw.cmux::<LOGB>(glwe, &rotate_glwe(glwe, mask_element))
+}
+
+pub fn blind_rotate<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
     const N: usize,
     const K: usize,
     const ELL: usize,
     const LOGB: usize,
 >(
-    s_lwe: &[F],
-    s_glwe: &[Poly<F, D, N>],
-    sigma: f64,
-) -> Vec<Ggsw<F, D, N, K, ELL>> {
-    s_lwe
-        .iter()
-        .map(|si| Ggsw::encrypt::<LOGB>(s_glwe, &Poly::constant(si), sigma).ntt_forward())
-        .collect()
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+) -> Glwe<F, D, N, K> {
+    // the body is negated before the mod switch, just like in the circuit
+    let acc_init = rotate_glwe(&Glwe::trivial_ct(testv.clone()), -ct[n]);
+    bsk.iter()
+        .zip(ct[..n].iter())
+        .fold(acc_init, |acc, (ggsw, ai)| {
+            blind_rotation_step::<F, D, LOGB, N, K, ELL>(&acc, ggsw, *ai)
+        })
+}
+
+// native (out-of-circuit) PBS computing the same output as `verified_pbs`
+pub fn pbs<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+    ksk: &Ggsw<F, D, N, K, ELL>,
+) -> Glwe<F, D, N, K> {
+    let acc = blind_rotate::<F, D, n, N, K, ELL, LOGB>(ct, testv, bsk);
+
+    // key switch
+    ksk.external_product::<LOGB>(&acc)
+}
+
+// native chain of PBSs computing the same output as `verified_pbs` with
+// `PbsCircuitOptions::chain`: each PBS after the first bootstraps the sample extraction of the
+// output of the previous one
+pub fn pbs_chain<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+    ksk: &Ggsw<F, D, N, K, ELL>,
+    num_pbs: usize,
+) -> Glwe<F, D, N, K> {
+    assert!(num_pbs > 0, "a chain has at least one PBS");
+    (1..num_pbs).fold(pbs::<F, D, n, N, K, ELL, LOGB>(ct, testv, bsk, ksk), |out_ct, _| {
+        pbs::<F, D, n, N, K, ELL, LOGB>(&out_ct.partial_sample_extract(n), testv, bsk, ksk)
+    })
+}
+
+// native counterpart of `vtfhe::key_switch_chunk`
+pub fn key_switch_chunk<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const LOGB: usize,
+    const ELL: usize,
+>(
+    lwe_ct: &[F],
+    mask: &[F],
+    ksk: &[[Vec<F>; ELL]],
+) -> Vec<F> {
+    let num_limbs = ceil_div_usize(F::BITS, LOGB);
+    mask.iter().zip(ksk).fold(lwe_ct.to_vec(), |acc, (ai, lev)| {
+        let limbs = decompose::<F, D, LOGB>(*ai, num_limbs);
+        limbs[num_limbs - ELL..]
+            .iter()
+            .zip(lev)
+            .fold(acc, |acc, (limb, lwe)| {
+                acc.iter().zip(lwe).map(|(x, y)| *x + *limb * *y).collect()
+            })
+    })
+}
+
+// native counterpart of `vtfhe::key_switch`: the LWE-to-LWE key switch of the sample extraction
+// of `glwe`
+pub fn key_switch<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    glwe: &Glwe<F, D, N, K>,
+    ksk: &LweKeySwitchKey<F, D, n, N, K, ELL>,
+) -> Vec<F> {
+    let mut init = vec![F::ZERO; n];
+    init.push(glwe.polys[K - 1].coeffs[0]);
+    key_switch_chunk::<F, D, LOGB, ELL>(&init, &glwe.flatten()[..(K - 1) * N], ksk.levs())
+}
+
+// native PBS with the LWE-to-LWE key switch, computing the same output as `verified_pbs` with
+// `KeySwitchMode::Lwe`
+pub fn pbs_lwe<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+    ksk: &LweKeySwitchKey<F, D, n, N, K, ELL>,
+) -> Vec<F> {
+    let acc = blind_rotate::<F, D, n, N, K, ELL, LOGB>(ct, testv, bsk);
+    key_switch::<F, D, n, N, K, ELL, LOGB>(&acc, ksk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::keys::{BootstrapKey, GlweSecretKey, KeySwitchKey, LweSecretKey};
+    use crate::vtfhe::crypto::lut::Lut;
+    use crate::vtfhe::crypto::lwe::get_delta;
+    use crate::vtfhe::crypto::parameter_set::{ParameterSet, TEST_TINY};
+    use crate::vtfhe::crypto::rng::test_rng;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, PrimeField64};
+    use rand::Rng;
+
+    #[test]
+    fn test_pbs() {
+        const PARAMS: ParameterSet = TEST_TINY;
+        const LOGB: usize = PARAMS.log_base;
+        const ELL: usize = PARAMS.num_limbs;
+        const N: usize = PARAMS.ring_dim;
+        const K: usize = PARAMS.glwe_dim;
+        const D: usize = 2;
+        const n: usize = PARAMS.lwe_dim;
+        const p: usize = PARAMS.plaintext_modulus;
+        type F = GoldilocksField;
+        let sigma_glwe = PARAMS.sigma_glwe;
+        let sigma_lwe = PARAMS.sigma_lwe;
+
+        let rng = &mut test_rng();
+        let s_to = GlweSecretKey::<F, D, N, K>::generate_partial::<n>(rng);
+        let s_lwe = s_to.to_lwe_key::<n>();
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, sigma_glwe, rng);
+        let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_to, sigma_lwe, rng);
+
+        let delta = get_delta::<F, D>(2 * p);
+        let testv = get_testv(p, delta);
+        let m = F::from_canonical_usize(rng.gen::<usize>() % p);
+        let ct = s_lwe.encrypt(&(delta * m), sigma_lwe, rng);
+
+        let out_ct = pbs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        let m_bar = s_to.decrypt(&out_ct).coeffs[0];
+        let m_out = ((m_bar.to_canonical_u64() as f64) / (delta.to_canonical_u64() as f64))
+            .round() as u64
+            % (2 * p as u64);
+        assert_eq!(m.to_canonical_u64(), m_out);
+    }
+
+    #[test]
+    fn test_pbs_lut() {
+        const LOGB: usize = 5;
+        const ELL: usize = 4;
+        const K: usize = 2;
+        const D: usize = 2;
+        const n: usize = 16;
+        const p: usize = 4;
+        type F = GoldilocksField;
+        let sigma_glwe = 4.99027217501041e-8;
+        let sigma_lwe = 0.0000117021618159313;
+
+        let rng = &mut test_rng();
+        let s_to = GlweSecretKey::<F, D, N, K>::generate_partial::<n>(rng);
+        let s_lwe = s_to.to_lwe_key::<n>();
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, sigma_glwe, rng);
+        let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_to, sigma_lwe, rng);
+        let delta = get_delta::<F, D>(2 * p);
+
+        for lut in Lut::ALL {
+            let testv = lut.testv::<F, D, N>(p, delta);
+            for m in 0..p {
+                let ct = s_lwe.encrypt(&(delta * F::from_canonical_usize(m)), sigma_lwe, rng);
+                let out_ct = pbs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+                let m_bar = s_to.decrypt(&out_ct).coeffs[0];
+                let m_out = ((m_bar.to_canonical_u64() as f64) / (delta.to_canonical_u64() as f64))
+                    .round() as usize
+                    % (2 * p);
+                assert_eq!(lut.eval(m, p), m_out, "{} failed for m = {m}", lut.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_pbs_lwe() {
+        const PARAMS: ParameterSet = TEST_TINY;
+        const LOGB: usize = PARAMS.log_base;
+        const ELL: usize = PARAMS.num_limbs;
+        const N: usize = PARAMS.ring_dim;
+        const K: usize = PARAMS.glwe_dim;
+        const D: usize = 2;
+        const n: usize = PARAMS.lwe_dim;
+        const p: usize = PARAMS.plaintext_modulus;
+        type F = GoldilocksField;
+        let sigma_glwe = PARAMS.sigma_glwe;
+        let sigma_lwe = PARAMS.sigma_lwe;
+
+        let rng = &mut test_rng();
+        let s_lwe = LweSecretKey::<F, D, n>::generate(rng);
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, sigma_glwe, rng);
+        let ksk = LweKeySwitchKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_lwe, sigma_lwe, rng);
+
+        let delta = get_delta::<F, D>(2 * p);
+        let testv = get_testv(p, delta);
+        let decode = |m_bar: F| {
+            ((m_bar.to_canonical_u64() as f64) / (delta.to_canonical_u64() as f64)).round() as u64
+                % (2 * p as u64)
+        };
+        let m = F::from_canonical_usize(rng.gen::<usize>() % p);
+        let ct = s_lwe.encrypt(&(delta * m), sigma_lwe, rng);
+
+        // the output is an LWE ciphertext under the input key, so it can be bootstrapped again
+        let out_ct = pbs_lwe::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), &ksk);
+        assert_eq!(out_ct.len(), n + 1);
+        assert_eq!(m.to_canonical_u64(), decode(s_lwe.decrypt(&out_ct)));
+        let out_ct = pbs_lwe::<F, D, n, N, K, ELL, LOGB>(&out_ct, &testv, bsk.ggsws(), &ksk);
+        assert_eq!(m.to_canonical_u64(), decode(s_lwe.decrypt(&out_ct)));
+    }
 }
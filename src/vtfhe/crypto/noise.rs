@@ -0,0 +1,241 @@
+use plonky2::hash::hash_types::RichField;
+use plonky2::util::ceil_div_usize;
+
+use super::parameter_set::ParameterSet;
+
+// An analytic model of the noise of the PBS, to choose parameters without running it. Like
+// `Glwe::get_max_error` and `lwe::get_error`, errors are relative to the field order, so a
+// variance is that of error / q. The model assumes binary keys and uniform masks, under which
+// the errors of the coefficients are independent and (close to) normal.
+
+// variance of a decomposition limb, centered in [-B/2, B/2]
+fn limb_variance(log_base: usize) -> f64 {
+    let base = (1u64 << log_base) as f64;
+    (base * base + 2.0) / 12.0
+}
+
+// variance of the error of approximating an element by its top ELL limbs, i.e. rounding it to
+// a multiple of B^(num_limbs - ELL)
+fn decomposition_variance<F: RichField>(params: &ParameterSet) -> f64 {
+    let num_limbs = ceil_div_usize(F::BITS, params.log_base);
+    let dropped_bits = params.log_base * num_limbs.saturating_sub(params.num_limbs);
+    let step = 2f64.powi(dropped_bits as i32) / F::ORDER as f64;
+    step * step / 12.0
+}
+
+// Variance added by an external product with a GGSW encrypting `m` (with noise `sigma`), for
+// the average of m^2 over the GGSWs (1/2 for the bits of a random binary key). The output
+// error is the error of the input times m plus this variance.
+pub fn external_product_variance<F: RichField>(params: &ParameterSet, sigma: f64, m_sq: f64) -> f64 {
+    let ring_dim = params.ring_dim as f64;
+    let glwe_dim = params.glwe_dim as f64;
+    // the limbs of the K polynomials times the errors of the K * ELL GLWE ciphertexts
+    let key_noise =
+        glwe_dim * params.num_limbs as f64 * ring_dim * limb_variance(params.log_base) * sigma * sigma;
+    // the decomposition errors of the body and of the K - 1 masks, times the binary GLWE key
+    let decomposition_noise =
+        m_sq * (1.0 + (glwe_dim - 1.0) * ring_dim / 2.0) * decomposition_variance::<F>(params);
+    key_noise + decomposition_noise
+}
+
+// Variance of the phase of the input ciphertext after the mod switch to 2N: its fresh noise
+// plus the rounding errors of the body and of the n mask elements (times the binary LWE key).
+// The PBS evaluates the LUT on the wrong block if this error exceeds a quarter of a block.
+pub fn mod_switch_variance(params: &ParameterSet) -> f64 {
+    let step = 1.0 / (2 * params.ring_dim) as f64;
+    let rounding = step * step / 12.0;
+    params.sigma_lwe * params.sigma_lwe + (1.0 + params.lwe_dim as f64 / 2.0) * rounding
+}
+
+// Variance of the accumulator after the blind rotation: n CMUXs, each of which adds an external
+// product with a GGSW of the bootstrapping key to the trivial (noiseless) initial accumulator
+pub fn blind_rotation_variance<F: RichField>(params: &ParameterSet) -> f64 {
+    params.lwe_dim as f64 * external_product_variance::<F>(params, params.sigma_glwe, 0.5)
+}
+
+// Variance added by the key switch, an external product with a GGSW whose GLevs encrypt the
+// GLWE key and 1 (see `KeySwitchKey`)
+pub fn key_switch_variance<F: RichField>(params: &ParameterSet) -> f64 {
+    external_product_variance::<F>(params, params.sigma_lwe, 1.0)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PbsNoise {
+    // variance of the phase of the input after the mod switch
+    pub mod_switch: f64,
+    // variances of the accumulator after the blind rotation and of the output ciphertext
+    pub blind_rotation: f64,
+    pub output: f64,
+    // log2 of the probability that the PBS, or decrypting its output, returns a wrong message
+    pub log2_failure_probability: f64,
+}
+
+impl PbsNoise {
+    pub fn estimate<F: RichField>(params: &ParameterSet) -> Self {
+        let mod_switch = mod_switch_variance(params);
+        let blind_rotation = blind_rotation_variance::<F>(params);
+        let output = blind_rotation + key_switch_variance::<F>(params);
+
+        // messages are encoded as multiples of 1 / 2p and decoded by rounding, both by the test
+        // vector (whose blocks are shifted by half a block) and by the decryption
+        let threshold = 1.0 / (4 * params.plaintext_modulus) as f64;
+        let ln_failure = ln_add(
+            ln_tail_probability(threshold, mod_switch),
+            ln_tail_probability(threshold, output),
+        );
+        PbsNoise {
+            mod_switch,
+            blind_rotation,
+            output,
+            log2_failure_probability: ln_failure / 2f64.ln(),
+        }
+    }
+
+    pub fn failure_probability(&self) -> f64 {
+        self.log2_failure_probability.exp2()
+    }
+}
+
+// ln P(|e| > threshold) for a centered normal e, i.e. ln erfc(threshold / sqrt(2 variance))
+fn ln_tail_probability(threshold: f64, variance: f64) -> f64 {
+    ln_erfc(threshold / (2.0 * variance).sqrt())
+}
+
+// ln(e^a + e^b) without underflow
+fn ln_add(a: f64, b: f64) -> f64 {
+    let (max, min) = if a > b { (a, b) } else { (b, a) };
+    max + (min - max).exp().ln_1p()
+}
+
+// ln erfc(x) for x >= 0, with the Chebyshev approximation of erfc in Numerical Recipes
+// (relative error below 1.2e-7), in logarithmic form so that tiny probabilities do not underflow
+fn ln_erfc(x: f64) -> f64 {
+    assert!(x >= 0.0, "ln_erfc expects a non-negative argument, got {x}");
+    let t = 1.0 / (1.0 + 0.5 * x);
+    let poly = [
+        -1.26551223,
+        1.00002368,
+        0.37409196,
+        0.09678418,
+        -0.18628806,
+        0.27886807,
+        -1.13520398,
+        1.48851587,
+        -0.82215223,
+        0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, c| acc * t + c);
+    t.ln() - x * x + poly
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vtfhe::crypto::ggsw::Ggsw;
+    use crate::vtfhe::crypto::glwe::Glwe;
+    use crate::vtfhe::crypto::keys::{BootstrapKey, GlweSecretKey, KeySwitchKey};
+    use crate::vtfhe::crypto::lwe::{get_delta, mod_switch_element};
+    use crate::vtfhe::crypto::parameter_set::{PARAM_128_BIT_P2, TEST_TINY};
+    use crate::vtfhe::crypto::poly::Poly;
+    use crate::vtfhe::crypto::{get_testv, pbs};
+    use crate::vtfhe::crypto::rng::test_rng;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Field64, PrimeField64};
+
+    const PARAMS: ParameterSet = TEST_TINY;
+    const LOGB: usize = PARAMS.log_base;
+    const ELL: usize = PARAMS.num_limbs;
+    const N: usize = PARAMS.ring_dim;
+    const K: usize = PARAMS.glwe_dim;
+    const D: usize = 2;
+    const n: usize = PARAMS.lwe_dim;
+    type F = GoldilocksField;
+
+    // the variance of the errors of the coefficients of a GLWE ciphertext of `m`
+    fn measured_variance(glwe: &Glwe<F, D, N, K>, s: &[Poly<F, D, N>], m: &Poly<F, D, N>) -> f64 {
+        let errors = glwe.decrypt(s).sub(m);
+        errors
+            .coeffs
+            .iter()
+            .map(|e| {
+                let e = e.to_canonical_u64();
+                let e = e.min(F::ORDER - e) as f64 / F::ORDER as f64;
+                e * e
+            })
+            .sum::<f64>()
+            / N as f64
+    }
+
+    fn assert_close(measured: f64, predicted: f64) {
+        // over the N = 1024 coefficients, the measured variance is typically within 20% of the model
+        assert!(
+            measured > 0.5 * predicted && measured < 2.0 * predicted,
+            "measured variance {measured:e}, predicted {predicted:e}"
+        );
+    }
+
+    #[test]
+    fn test_external_product_noise() {
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
+        let ggsw = Ggsw::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &Poly::constant(&F::ONE), PARAMS.sigma_glwe, rng)
+            .ntt_forward();
+        let m = Poly::<F, D, N>::rand(rng);
+        let glwe = Glwe::<F, D, N, K>::encrypt(&s, &m, 0.0, rng);
+
+        let out = ggsw.external_product::<LOGB>(&glwe);
+        assert_close(
+            measured_variance(&out, &s, &m),
+            external_product_variance::<F>(&PARAMS, PARAMS.sigma_glwe, 1.0),
+        );
+    }
+
+    #[test]
+    fn test_pbs_noise() {
+        let p = PARAMS.plaintext_modulus;
+        let rng = &mut test_rng();
+        let s_to = GlweSecretKey::<F, D, N, K>::generate_partial::<n>(rng);
+        let s_lwe = s_to.to_lwe_key::<n>();
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, PARAMS.sigma_glwe, rng);
+        let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_to, PARAMS.sigma_lwe, rng);
+
+        let delta = get_delta::<F, D>(2 * p);
+        let testv: Poly<F, D, N> = get_testv(p, delta);
+        let ct = s_lwe.encrypt(&(delta * F::ONE), PARAMS.sigma_lwe, rng);
+        let out_ct = pbs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+
+        // without noise, the output would be the test vector rotated by the mod-switched phase
+        let shift = s_lwe
+            .elements()
+            .iter()
+            .zip(&ct[..n])
+            .filter(|(si, _)| si.is_one())
+            .fold(mod_switch_element::<F, D>(-ct[n], N), |acc, (_, ai)| {
+                acc + mod_switch_element::<F, D>(*ai, N)
+            });
+        let expected = testv.rotate(shift);
+        let noise = PbsNoise::estimate::<F>(&PARAMS);
+        assert_close(measured_variance(&out_ct, s_to.polys(), &expected), noise.output);
+    }
+
+    #[test]
+    fn test_failure_probability() {
+        // erfc(1) = 0.157299..., erfc(5) = 1.5375e-12
+        assert!((ln_erfc(1.0).exp() - 0.1572992).abs() < 1e-6);
+        assert!((ln_erfc(5.0).exp() / 1.5374597944e-12 - 1.0).abs() < 1e-6);
+
+        // the paper's parameters fail with a negligible probability, dominated by the key switch
+        let noise = PbsNoise::estimate::<F>(&PARAM_128_BIT_P2);
+        assert!(noise.output > noise.blind_rotation);
+        assert!(noise.log2_failure_probability < -64.0, "{noise:?}");
+
+        // which grows with the noise
+        let noisy = ParameterSet { sigma_lwe: 2e-4, ..PARAM_128_BIT_P2 };
+        let noisy = PbsNoise::estimate::<F>(&noisy);
+        assert!(noisy.log2_failure_probability > noise.log2_failure_probability);
+        assert!(noisy.failure_probability() > 1e-6, "{noisy:?}");
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{anyhow, ensure, Result};
+
+// Named sets of TFHE parameters. The PBS code takes the parameters as const generics, so a set
+// is selected at compile time (see `PARAMS` in lib.rs), e.g. `const n: usize = PARAMS.lwe_dim`.
+// The id of a set is recorded in the files written for it, so files written for another set
+// are rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterSet {
+    pub id: &'static str,
+    pub lwe_dim: usize,           // n
+    pub ring_dim: usize,          // N
+    pub glwe_dim: usize,          // K (= k + 1)
+    pub num_limbs: usize,         // ELL
+    pub log_base: usize,          // LOGB
+    pub plaintext_modulus: usize, // p
+    pub sigma_glwe: f64,          // GLWE noise (bootstrapping key)
+    pub sigma_lwe: f64,           // LWE noise (input ciphertext and key switching key)
+    // estimated security level in bits, None for insecure parameters
+    pub security_bits: Option<u32>,
+}
+
+// The optimized parameters of the paper (Towards Verifiable FHE in Practice), for p = 2
+pub const PARAM_128_BIT_P2: ParameterSet = ParameterSet {
+    id: "PARAM_128_BIT_P2",
+    lwe_dim: 728,
+    ring_dim: 1024,
+    glwe_dim: 2,
+    num_limbs: 4,
+    log_base: 5,
+    plaintext_modulus: 2,
+    sigma_glwe: 4.99027217501041e-8,
+    sigma_lwe: 0.0000117021618159313,
+    security_bits: Some(128),
+};
+
+// A short LWE key for fast tests, not secure
+pub const TEST_TINY: ParameterSet = ParameterSet {
+    id: "TEST_TINY",
+    lwe_dim: 16,
+    security_bits: None,
+    ..PARAM_128_BIT_P2
+};
+
+pub const PARAMETER_SETS: [ParameterSet; 2] = [PARAM_128_BIT_P2, TEST_TINY];
+
+// the longest id, so that it fits in the header of a binary container
+pub const MAX_ID_LEN: usize = 16;
+
+impl ParameterSet {
+    pub fn by_id(id: &str) -> Result<ParameterSet> {
+        PARAMETER_SETS
+            .into_iter()
+            .find(|params| params.id == id)
+            .ok_or_else(|| {
+                let ids: Vec<&str> = PARAMETER_SETS.iter().map(|params| params.id).collect();
+                anyhow!("unknown parameter set '{id}', expected one of: {}", ids.join(", "))
+            })
+    }
+
+    // Checks that the parameters are consistent with each other and with the 64-bit field
+    pub fn check(&self) -> Result<()> {
+        let id = self.id;
+        ensure!(
+            !id.is_empty() && id.len() <= MAX_ID_LEN && id.is_ascii(),
+            "the id '{id}' should have between 1 and {MAX_ID_LEN} ASCII characters"
+        );
+        ensure!(
+            self.ring_dim.is_power_of_two() && self.ring_dim >= 2,
+            "{id}: the ring dimension N = {} is not a power of two",
+            self.ring_dim
+        );
+        ensure!(self.glwe_dim >= 2, "{id}: the GLWE dimension K = {} should be at least 2", self.glwe_dim);
+        ensure!(
+            self.num_limbs >= 1 && self.log_base >= 1 && self.num_limbs * self.log_base <= 64,
+            "{id}: ELL * LOGB = {} * {} should be between 1 and 64, the bits of a field element",
+            self.num_limbs,
+            self.log_base
+        );
+        // the LWE key is extracted from the first n coefficients of the GLWE key, see
+        // `Glwe::partial_key`
+        ensure!(
+            self.lwe_dim >= 1 && self.lwe_dim <= (self.glwe_dim - 1) * self.ring_dim,
+            "{id}: the LWE dimension n = {} should be between 1 and (K - 1) * N = {}",
+            self.lwe_dim,
+            (self.glwe_dim - 1) * self.ring_dim
+        );
+        // the test vector has a block of N / p coefficients per message
+        ensure!(
+            self.plaintext_modulus >= 2 && self.ring_dim.is_multiple_of(self.plaintext_modulus),
+            "{id}: the plaintext modulus p = {} should be at least 2 and divide N = {}",
+            self.plaintext_modulus,
+            self.ring_dim
+        );
+        ensure!(
+            self.sigma_glwe >= 0.0 && self.sigma_lwe >= 0.0,
+            "{id}: the noise parameters should be non-negative"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameter_sets() {
+        for params in PARAMETER_SETS {
+            params.check().unwrap();
+            assert_eq!(ParameterSet::by_id(params.id).unwrap(), params);
+        }
+        assert!(ParameterSet::by_id("PARAM_0_BIT").is_err());
+
+        let err = |params: ParameterSet| params.check().unwrap_err().to_string();
+        assert!(err(ParameterSet { ring_dim: 1000, ..TEST_TINY }).contains("not a power of two"));
+        assert!(err(ParameterSet { num_limbs: 13, ..TEST_TINY }).contains("ELL * LOGB"));
+        assert!(err(ParameterSet { lwe_dim: 1025, ..TEST_TINY }).contains("(K - 1) * N = 1024"));
+        assert!(err(ParameterSet { plaintext_modulus: 3, ..TEST_TINY }).contains("divide N"));
+        assert!(err(ParameterSet { id: "A_VERY_LONG_PARAMETER_SET", ..TEST_TINY }).contains("ASCII"));
+    }
+}
@@ -1,18 +1,19 @@
 use std::array::from_fn;
 
 use plonky2::{field::extension::Extendable, hash::hash_types::RichField};
+use rand::{CryptoRng, Rng, RngCore};
 
-use crate::ntt::params;
+use crate::ntt::ntt_params;
 
 use super::lwe::error_sample;
 
-fn ntt_fw_update<F: RichField + Extendable<D>, const D: usize>(input: &[F], m: usize) -> Vec<F> {
+fn ntt_fw_update<F: RichField + Extendable<D>, const D: usize>(input: &[F], roots: &[u64], m: usize) -> Vec<F> {
     let mut a = input.to_vec();
-    let t = params::N / (2 * m);
+    let t = input.len() / (2 * m);
     for i in 0..m {
         let j1 = 2 * i * t;
         let j2 = j1 + t;
-        let root = params::ROOTS[m + i];
+        let root = roots[m + i];
         let s = F::from_canonical_u64(root);
         for j in j1..j2 {
             let u = a[j];
@@ -24,22 +25,24 @@ fn ntt_fw_update<F: RichField + Extendable<D>, const D: usize>(input: &[F], m: u
     a
 }
 
-pub fn ntt_forward<F: RichField + Extendable<D>, const D: usize>(input: &[F]) -> Vec<F> {
+pub fn ntt_forward<F: RichField + Extendable<D>, const D: usize, const N: usize>(input: &[F]) -> Vec<F> {
+    assert_eq!(input.len(), N);
+    let params = ntt_params::<F, N>();
     let mut current = input.to_vec();
-    for m in (0..params::LOGN).map(|i| 2usize.pow(i)) {
-        current = ntt_fw_update(&current, m);
+    for m in (0..params.logn).map(|i| 2usize.pow(i)) {
+        current = ntt_fw_update::<F, D>(&current, params.roots, m);
     }
 
     current
 }
 
-fn ntt_bw_update<F: RichField + Extendable<D>, const D: usize>(input: &[F], m: usize) -> Vec<F> {
+fn ntt_bw_update<F: RichField + Extendable<D>, const D: usize>(input: &[F], invroots: &[u64], m: usize) -> Vec<F> {
     let mut a = input.to_vec();
-    let t = params::N / (2 * m);
+    let t = input.len() / (2 * m);
     let mut j1 = 0usize;
     for i in 0..m {
         let j2 = j1 + t;
-        let root = params::INVROOTS[m + i];
+        let root = invroots[m + i];
         let s = F::from_canonical_u64(root);
         for j in j1..j2 {
             let u = a[j];
@@ -53,37 +56,66 @@ fn ntt_bw_update<F: RichField + Extendable<D>, const D: usize>(input: &[F], m: u
     a
 }
 
-pub fn ntt_backward<F: RichField + Extendable<D>, const D: usize>(input: &[F]) -> Vec<F> {
+pub fn ntt_backward<F: RichField + Extendable<D>, const D: usize, const N: usize>(input: &[F]) -> Vec<F> {
+    assert_eq!(input.len(), N);
+    let params = ntt_params::<F, N>();
     let mut current = input.to_vec();
-    for m in (0..params::LOGN).rev().map(|i| 2usize.pow(i)) {
-        current = ntt_bw_update(&current, m);
+    for m in (0..params.logn).rev().map(|i| 2usize.pow(i)) {
+        current = ntt_bw_update::<F, D>(&current, params.invroots, m);
     }
 
-    let n_inv = F::from_canonical_u64(params::NINV);
+    let n_inv = F::from_canonical_u64(params.ninv);
     current.into_iter().map(|g| g * n_inv).collect()
 }
 
+/// Split the given element into a list of base-B limbs (centered), with little-endian
+/// ordering. This mirrors `glwe_poly::decompose` so that both produce identical limbs.
+pub fn decompose<F: RichField + Extendable<D>, const D: usize, const LOGB: usize>(
+    x: F,
+    num_limbs: usize,
+) -> Vec<F> {
+    let num_bits = num_limbs * LOGB;
+    let x64 = x.to_canonical_u64();
+    let sgn = num_bits <= 64 && (x64 >> (num_bits - 1)) & 1 == 1;
+    let x_centered_lift = if sgn { (-x).to_canonical_u64() } else { x64 } as u128;
+    let mask = (1u128 << LOGB) - 1;
+    let mut carry = 0i64;
+    (0..num_limbs)
+        .map(|i| {
+            let k = ((x_centered_lift >> (i * LOGB)) & mask) as i64;
+            let k_w_carry = k + carry;
+            carry = k >> (LOGB - 1);
+            let balanced_k = F::from_noncanonical_i64(k_w_carry - (carry << LOGB));
+            if sgn {
+                -balanced_k
+            } else {
+                balanced_k
+            }
+        })
+        .collect()
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Poly<F: RichField + Extendable<D>, const D: usize, const N: usize> {
     pub coeffs: [F; N],
 }
 
 impl<F: RichField + Extendable<D>, const D: usize, const N: usize> Poly<F, D, N> {
-    pub fn rand() -> Self {
+    pub fn rand(rng: &mut (impl RngCore + CryptoRng)) -> Self {
         Poly {
-            coeffs: from_fn(|_| F::rand()),
+            coeffs: from_fn(|_| F::sample(rng)),
         }
     }
 
-    pub fn rand_bin() -> Self {
+    pub fn rand_bin(rng: &mut (impl RngCore + CryptoRng)) -> Self {
         Poly {
-            coeffs: from_fn(|_| F::from_canonical_u64(rand::random::<u64>() % 2)),
+            coeffs: from_fn(|_| F::from_canonical_u64(rng.gen::<u64>() % 2)),
         }
     }
 
-    pub fn rand_error(sigma: f64) -> Self {
+    pub fn rand_error(sigma: f64, rng: &mut (impl RngCore + CryptoRng)) -> Self {
         Poly {
-            coeffs: from_fn(|_| error_sample(sigma)),
+            coeffs: from_fn(|_| error_sample(sigma, rng)),
         }
     }
 
@@ -122,17 +154,33 @@ impl<F: RichField + Extendable<D>, const D: usize, const N: usize> Poly<F, D, N>
         }
     }
 
+    // multiplication by X^shift
+    pub fn rotate(&self, shift: usize) -> Self {
+        self.right_shift(shift % (2 * N))
+    }
+
+    pub fn decompose<const LOGB: usize>(&self, num_limbs: usize) -> Vec<Self> {
+        let decomps: Vec<Vec<F>> = self
+            .coeffs
+            .iter()
+            .map(|xi| decompose::<F, D, LOGB>(*xi, num_limbs))
+            .collect();
+        (0..num_limbs)
+            .map(|i| Poly {
+                coeffs: from_fn(|j| decomps[j][i]),
+            })
+            .collect()
+    }
+
     pub fn ntt_fw(&self) -> Self {
-        assert_eq!(N, params::N);
         Poly {
-            coeffs: ntt_forward(&self.coeffs).try_into().unwrap(),
+            coeffs: ntt_forward::<F, D, N>(&self.coeffs).try_into().unwrap(),
         }
     }
 
     pub fn ntt_bw(&self) -> Self {
-        assert_eq!(N, params::N);
         Poly {
-            coeffs: ntt_backward(&self.coeffs).try_into().unwrap(),
+            coeffs: ntt_backward::<F, D, N>(&self.coeffs).try_into().unwrap(),
         }
     }
 
@@ -187,23 +235,76 @@ impl<F: RichField + Extendable<D>, const D: usize, const N: usize> Poly<F, D, N>
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ntt::params::{N, TESTG, TESTGHAT};
+    use crate::vtfhe::crypto::rng::test_rng;
     use plonky2::field::goldilocks_field::GoldilocksField;
-    use plonky2::field::types::Field;
+    use plonky2::field::types::{Field, Sample};
 
-    #[test]
-    fn test_ntt() {
+    fn check_ntt<const N: usize>() {
         const D: usize = 2;
         type F = GoldilocksField;
+        let params = ntt_params::<F, N>();
         let test = Poly::<F, D, N> {
-            coeffs: TESTG.map(|x| F::from_canonical_u64(x)),
+            coeffs: from_fn(|i| F::from_canonical_u64(params.testg[i])),
         };
 
         let test_hat = Poly::<F, D, N> {
-            coeffs: TESTGHAT.map(|x| F::from_canonical_u64(x)),
+            coeffs: from_fn(|i| F::from_canonical_u64(params.testghat[i])),
         };
 
         assert_eq!(test.ntt_fw().coeffs, test_hat.coeffs);
         assert_eq!(test_hat.ntt_bw().coeffs, test.coeffs);
     }
+
+    #[test]
+    fn test_ntt() {
+        check_ntt::<8>();
+        check_ntt::<16>();
+        check_ntt::<32>();
+        check_ntt::<64>();
+        check_ntt::<128>();
+        check_ntt::<256>();
+        check_ntt::<512>();
+        check_ntt::<1024>();
+        check_ntt::<2048>();
+    }
+
+    #[test]
+    fn test_mul_ring_sizes() {
+        const D: usize = 2;
+        type F = GoldilocksField;
+
+        // the NTT-based product agrees with the negacyclic schoolbook product for any N
+        fn check_mul<const N: usize>(rng: &mut (impl RngCore + CryptoRng)) {
+            let a = Poly::<F, D, N>::rand(rng);
+            let b = Poly::<F, D, N>::rand(rng);
+            let expected = (0..N).fold(Poly::<F, D, N>::constant(&F::ZERO), |acc, i| {
+                acc.add(&b.right_shift(i).scalar_mul(&a.coeffs[i]))
+            });
+            assert_eq!(a.mul(&b), expected);
+        }
+        let rng = &mut test_rng();
+        check_mul::<8>(rng);
+        check_mul::<64>(rng);
+        check_mul::<1024>(rng);
+    }
+
+    #[test]
+    fn test_decompose() {
+        const D: usize = 2;
+        type F = GoldilocksField;
+
+        for (logb, num_limbs) in [(8, 8), (5, 13)] {
+            let x = F::rand();
+            let limbs = match logb {
+                8 => decompose::<F, D, 8>(x, num_limbs),
+                _ => decompose::<F, D, 5>(x, num_limbs),
+            };
+            let base = F::TWO.exp_u64(logb as u64);
+            let sum = limbs
+                .iter()
+                .enumerate()
+                .fold(F::ZERO, |acc, (i, limb)| acc + *limb * base.exp_u64(i as u64));
+            assert_eq!(x, sum, "Recombined value does not match: {:?}", limbs);
+        }
+    }
 }
@@ -0,0 +1,19 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+// Every sampling function of key generation and encryption takes its RNG as an argument
+// (`rng: &mut (impl RngCore + CryptoRng)`), so that a run is determined by the seed of its RNG.
+// The commands use a ChaCha20 RNG, seeded with `vfhe --seed` for reproducible runs and from the
+// OS otherwise.
+pub fn new_rng(seed: Option<u64>) -> ChaCha20Rng {
+    match seed {
+        Some(seed) => ChaCha20Rng::seed_from_u64(seed),
+        None => ChaCha20Rng::from_entropy(),
+    }
+}
+
+// the RNG of the tests, with a fixed seed so that a failure can be reproduced
+#[cfg(test)]
+pub(crate) fn test_rng() -> ChaCha20Rng {
+    ChaCha20Rng::seed_from_u64(0)
+}
@@ -0,0 +1,353 @@
+use std::fmt::Display;
+
+use plonky2::{field::extension::Extendable, hash::hash_types::RichField};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use super::compressed::{CompressedGgsw, CompressedGlwe, Seed};
+use super::{ggsw::Ggsw, glev::Glev, glwe::Glwe, poly::Poly};
+
+// serde support for keys and ciphertexts. Field elements are written as canonical u64s and the
+// structures as nested arrays (a GGSW is an array of K GLevs, a GLev of ELL GLWEs, a GLWE of K
+// polynomials, a polynomial of N coefficients) and the compressed ciphertexts as their seed (an
+// array of bytes) and bodies. Reading checks every length against the const
+// generics and rejects non-canonical elements, so a malformed file is an error rather than a
+// panic; use serde_path_to_error to locate it (e.g. `bsk[3][1][0][1]: expected ...`).
+
+fn check_len<E: de::Error>(expected: usize, actual: usize, what: impl Display) -> Result<(), E> {
+    if actual != expected {
+        return Err(E::custom(format!("expected {expected} {what}, found {actual}")));
+    }
+    Ok(())
+}
+
+fn canonical_elements<F: RichField, E: de::Error>(xs: Vec<u64>) -> Result<Vec<F>, E> {
+    xs.into_iter()
+        .enumerate()
+        .map(|(i, x)| {
+            if x < F::ORDER {
+                Ok(F::from_canonical_u64(x))
+            } else {
+                Err(E::custom(format!(
+                    "non-canonical field element {x} at index {i} (the field order is {})",
+                    F::ORDER
+                )))
+            }
+        })
+        .collect()
+}
+
+fn into_array<T, E: de::Error, const L: usize>(items: Vec<T>, what: &str) -> Result<[T; L], E> {
+    check_len(L, items.len(), what)?;
+    Ok(items.try_into().unwrap_or_else(|_| unreachable!()))
+}
+
+// `serialize_with` for a list of field elements, e.g. an LWE ciphertext
+pub fn serialize_elements<F: RichField, S: Serializer>(xs: &[F], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(xs.iter().map(|x| x.to_canonical_u64()))
+}
+
+// `deserialize_with` for a list of LEN field elements, e.g. an LWE ciphertext of dimension n
+// with `deserialize_elements::<_, _, { n + 1 }>`
+pub fn deserialize_elements<'de, De: Deserializer<'de>, F: RichField, const LEN: usize>(
+    deserializer: De,
+) -> Result<Vec<F>, De::Error> {
+    let xs: Vec<u64> = Vec::deserialize(deserializer)?;
+    check_len(LEN, xs.len(), "field elements")?;
+    canonical_elements(xs)
+}
+
+// `deserialize_with` for a list of LEN items, e.g. a bootstrapping key of n GGSWs
+pub fn deserialize_vec<'de, De: Deserializer<'de>, T: Deserialize<'de>, const LEN: usize>(
+    deserializer: De,
+) -> Result<Vec<T>, De::Error> {
+    let items: Vec<T> = Vec::deserialize(deserializer)?;
+    check_len(LEN, items.len(), "items")?;
+    Ok(items)
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const N: usize> Serialize for Poly<F, D, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_elements(&self.coeffs, serializer)
+    }
+}
+
+impl<'de, F: RichField + Extendable<D>, const D: usize, const N: usize> Deserialize<'de>
+    for Poly<F, D, N>
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let coeffs: Vec<u64> = Vec::deserialize(deserializer)?;
+        Ok(Poly {
+            coeffs: into_array(canonical_elements(coeffs)?, "coefficients in a polynomial")?,
+        })
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize> Serialize
+    for Glwe<F, D, N, K>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.polys)
+    }
+}
+
+impl<'de, F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>
+    Deserialize<'de> for Glwe<F, D, N, K>
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let polys: Vec<Poly<F, D, N>> = Vec::deserialize(deserializer)?;
+        Ok(Glwe {
+            polys: into_array(polys, "polynomials in a GLWE ciphertext")?,
+        })
+    }
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > Serialize for Glev<F, D, N, K, ELL>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.glwes)
+    }
+}
+
+impl<
+        'de,
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > Deserialize<'de> for Glev<F, D, N, K, ELL>
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let glwes: Vec<Glwe<F, D, N, K>> = Vec::deserialize(deserializer)?;
+        Ok(Glev {
+            glwes: into_array(glwes, "GLWE ciphertexts in a GLev")?,
+        })
+    }
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > Serialize for Ggsw<F, D, N, K, ELL>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.glevs)
+    }
+}
+
+impl<
+        'de,
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > Deserialize<'de> for Ggsw<F, D, N, K, ELL>
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        let glevs: Vec<Glev<F, D, N, K, ELL>> = Vec::deserialize(deserializer)?;
+        Ok(Ggsw {
+            glevs: into_array(glevs, "GLevs in a GGSW")?,
+        })
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize> Serialize
+    for CompressedGlwe<F, D, N, K>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CompressedGlwe", 2)?;
+        state.serialize_field("seed", &self.seed)?;
+        state.serialize_field("body", &self.body)?;
+        state.end()
+    }
+}
+
+impl<'de, F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>
+    Deserialize<'de> for CompressedGlwe<F, D, N, K>
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(bound = "")]
+        struct Fields<F: RichField + Extendable<D>, const D: usize, const N: usize> {
+            seed: Seed,
+            body: Poly<F, D, N>,
+        }
+        let Fields { seed, body } = Fields::deserialize(deserializer)?;
+        Ok(CompressedGlwe { seed, body })
+    }
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > Serialize for CompressedGgsw<F, D, N, K, ELL>
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bodies: Vec<&[Poly<F, D, N>]> = self.bodies.iter().map(|glev| glev.as_slice()).collect();
+        let mut state = serializer.serialize_struct("CompressedGgsw", 2)?;
+        state.serialize_field("seed", &self.seed)?;
+        state.serialize_field("bodies", &bodies)?;
+        state.end()
+    }
+}
+
+impl<
+        'de,
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > Deserialize<'de> for CompressedGgsw<F, D, N, K, ELL>
+{
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+        // the K lists of ELL bodies, one per GLev
+        struct Bodies<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize, const ELL: usize>(
+            [[Poly<F, D, N>; ELL]; K],
+        );
+
+        impl<
+                'de,
+                F: RichField + Extendable<D>,
+                const D: usize,
+                const N: usize,
+                const K: usize,
+                const ELL: usize,
+            > Deserialize<'de> for Bodies<F, D, N, K, ELL>
+        {
+            fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self, De::Error> {
+                let glevs: Vec<Vec<Poly<F, D, N>>> = Vec::deserialize(deserializer)?;
+                let glevs: Vec<[Poly<F, D, N>; ELL]> = glevs
+                    .into_iter()
+                    .map(|bodies| into_array(bodies, "bodies in a compressed GLev"))
+                    .collect::<Result<_, _>>()?;
+                Ok(Bodies(into_array(glevs, "GLevs in a compressed GGSW")?))
+            }
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(bound = "")]
+        struct Fields<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize, const ELL: usize> {
+            seed: Seed,
+            bodies: Bodies<F, D, N, K, ELL>,
+        }
+        let Fields { seed, bodies } = Fields::deserialize(deserializer)?;
+        Ok(CompressedGgsw {
+            seed,
+            bodies: bodies.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::array::from_fn;
+
+    use plonky2::field::types::Sample;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::vtfhe::crypto::rng::test_rng;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Inputs {
+        #[serde(
+            serialize_with = "serialize_elements",
+            deserialize_with = "deserialize_elements::<_, _, 5>"
+        )]
+        ct: Vec<F>,
+        #[serde(deserialize_with = "deserialize_vec::<_, _, 2>")]
+        bsk: Vec<Ggsw<F, D, 4, 2, 3>>,
+        out_ct: Glwe<F, D, 4, 2>,
+    }
+
+    fn parse(json: &str) -> Result<Inputs, String> {
+        serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(json))
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_serde_validation() {
+        let rng = &mut test_rng();
+        let mut glwe = || Glwe {
+            polys: from_fn(|_| Poly::rand(rng)),
+        };
+        let mut ggsw = || Ggsw {
+            glevs: from_fn(|_| Glev {
+                glwes: from_fn(|_| glwe()),
+            }),
+        };
+        let inputs = Inputs {
+            ct: F::rand_vec(5),
+            bsk: vec![ggsw(), ggsw()],
+            out_ct: glwe(),
+        };
+        let json = serde_json::to_string(&inputs).unwrap();
+        let parsed = parse(&json).unwrap();
+        assert_eq!(parsed.ct, inputs.ct);
+        assert_eq!(parsed.bsk, inputs.bsk);
+        assert_eq!(parsed.out_ct, inputs.out_ct);
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let malformed = |path: &str, x: serde_json::Value| {
+            let mut value = value.clone();
+            *value.pointer_mut(path).unwrap() = x;
+            parse(&value.to_string()).unwrap_err()
+        };
+
+        let err = malformed("/bsk/1/0/2/1", serde_json::json!([1, 2, 3]));
+        assert!(err.starts_with("bsk[1][0][2][1]: expected 4 coefficients"), "{err}");
+        let err = malformed("/bsk/1/0/2/1/3", serde_json::json!(u64::MAX));
+        assert!(err.starts_with("bsk[1][0][2][1]: non-canonical field element"), "{err}");
+        let err = malformed("/bsk/0/1", serde_json::json!([]));
+        assert!(err.starts_with("bsk[0][1]: expected 3 GLWE ciphertexts"), "{err}");
+        let err = malformed("/out_ct", serde_json::json!([[0, 0, 0, 0]]));
+        assert!(err.starts_with("out_ct: expected 2 polynomials"), "{err}");
+        let err = malformed("/ct", serde_json::json!([0, 0]));
+        assert!(err.starts_with("ct: expected 5 field elements, found 2"), "{err}");
+
+        value["bsk"].as_array_mut().unwrap().pop();
+        let err = parse(&value.to_string()).unwrap_err();
+        assert!(err.starts_with("bsk: expected 2 items, found 1"), "{err}");
+    }
+
+    #[test]
+    fn test_compressed_serde() {
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, 4, 2>::key_gen(rng);
+        let m = Poly::rand(rng);
+        let glwe = CompressedGlwe::<F, D, 4, 2>::encrypt(&s, &m, 0f64, rng);
+        let ggsw = CompressedGgsw::<F, D, 4, 2, 3>::encrypt::<8>(&s, &m, 0f64, rng);
+
+        let json = serde_json::to_string(&glwe).unwrap();
+        assert_eq!(serde_json::from_str::<CompressedGlwe<F, D, 4, 2>>(&json).unwrap(), glwe);
+        let json = serde_json::to_string(&ggsw).unwrap();
+        assert_eq!(serde_json::from_str::<CompressedGgsw<F, D, 4, 2, 3>>(&json).unwrap(), ggsw);
+
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value["bodies"][1].as_array_mut().unwrap().pop();
+        let err = serde_json::from_str::<CompressedGgsw<F, D, 4, 2, 3>>(&value.to_string()).unwrap_err();
+        assert!(err.to_string().starts_with("expected 3 bodies in a compressed GLev"), "{err}");
+        value["seed"] = serde_json::json!([0, 1]);
+        assert!(serde_json::from_str::<CompressedGgsw<F, D, 4, 2, 3>>(&value.to_string()).is_err());
+    }
+}
@@ -125,12 +125,18 @@ mod tests {
     };
 
     use crate::ntt::params::N;
+    use crate::vtfhe::crypto::rng::test_rng;
     use crate::vtfhe::{
-        crypto::{glwe::Glwe, poly::Poly},
+        crypto::{
+            glwe::Glwe,
+            keys::{GlweSecretKey, KeySwitchKey},
+            poly::Poly,
+        },
         glwe_ct::GlweCt,
     };
 
     use super::*;
+    use rand::Rng;
     #[test]
     fn test_external_product() {
         const LOGB: usize = 8;
@@ -150,15 +156,16 @@ mod tests {
         glwe.register(&mut builder);
         ggsw.register(&mut builder);
 
-        let s = Glwe::<F, D, N, K>::key_gen();
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
         let m_glwe = Poly::<F, D, N> {
             coeffs: from_fn(|i| F::from_canonical_usize(i)),
         };
-        let bit = F::from_canonical_u64(rand::random::<u64>() % 2);
+        let bit = F::from_canonical_u64(rng.gen::<u64>() % 2);
         let m_ggsw = Poly::<F, D, N>::constant(&bit);
 
-        let ct_glwe = Glwe::<F, D, N, K>::encrypt(&s, &m_glwe, 0f64);
-        let ct_ggsw = Ggsw::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &m_ggsw, 0f64).ntt_forward();
+        let ct_glwe = Glwe::<F, D, N, K>::encrypt(&s, &m_glwe, 0f64, rng);
+        let ct_ggsw = Ggsw::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &m_ggsw, 0f64, rng).ntt_forward();
 
         glwe.assign(&mut pw, &ct_glwe);
         ggsw.assign(&mut pw, &ct_ggsw);
@@ -173,6 +180,7 @@ mod tests {
         let start = GlweCt::<N, K>::num_targets() + GgswCt::<N, K, ELL>::num_targets();
         let out_glwe_slice = &proof.public_inputs[start..start+GlweCt::<N, K>::num_targets()];
         let out_glwe = Glwe::<F, D, N, K>::from_slice(&out_glwe_slice);
+        assert_eq!(out_glwe, ct_ggsw.external_product::<LOGB>(&ct_glwe));
         let m_out = out_glwe.decrypt(&s);
         assert_eq!(m_glwe.scalar_mul(&bit), m_out);
     }
@@ -196,15 +204,16 @@ mod tests {
         glwe.register(&mut builder);
         ggsw.register(&mut builder);
 
-        let s_to = Glwe::<F, D, N, K>::key_gen();
-        let s_from = Glwe::<F, D, N, K>::key_gen();
-        let ksk = Ggsw::<F, D, N, K, ELL>::compute_ksk::<LOGB>(&s_to, &s_from, 0f64);
+        let rng = &mut test_rng();
+        let s_to = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let s_from = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_from, &s_to, 0f64, rng);
 
-        let m_glwe = Poly::<F, D, N>::rand();
-        let ct_glwe = Glwe::<F, D, N, K>::encrypt(&s_from, &m_glwe, 0f64);
+        let m_glwe = Poly::<F, D, N>::rand(rng);
+        let ct_glwe = s_from.encrypt(&m_glwe, 0f64, rng);
 
         glwe.assign(&mut pw, &ct_glwe);
-        ggsw.assign(&mut pw, &ksk);
+        ggsw.assign(&mut pw, ksk.ggsw());
 
         let z = ggsw.external_product::<F, D, LOGB>(&mut builder, &glwe);
         z.register(&mut builder);
@@ -216,7 +225,8 @@ mod tests {
         let start = GlweCt::<N, K>::num_targets() + GgswCt::<N, K, ELL>::num_targets();
         let out_glwe_slice = &proof.public_inputs[start..start+GlweCt::<N, K>::num_targets()];
         let out_glwe = Glwe::<F, D, N, K>::from_slice(&out_glwe_slice);
-        let m_out = out_glwe.decrypt(&s_to);
+        assert_eq!(out_glwe, ksk.ggsw().external_product::<LOGB>(&ct_glwe));
+        let m_out = s_to.decrypt(&out_glwe);
         assert_eq!(m_glwe, m_out);
     }
 }
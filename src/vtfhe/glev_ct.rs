@@ -98,7 +98,7 @@ impl<const N: usize, const K: usize, const ELL: usize> GlevCt<N, K, ELL> {
         let limbs = glwe_poly.decompose::<F, D, LOGB>(cb, num_limbs);
         let limbs_hat = &limbs[num_limbs - ELL..]
             .iter()
-            .map(|limb| ntt_forward(cb, &limb))
+            .map(|limb| ntt_forward::<F, D, N>(cb, limb))
             .collect();
         let range: [usize; K] = core::array::from_fn(|i| i);
         let polys = range.map(|index| GlwePoly {
@@ -117,6 +117,7 @@ mod tests {
     use crate::vtfhe::crypto::glwe::Glwe;
     use crate::vtfhe::crypto::lwe::decrypt;
     use crate::vtfhe::crypto::poly::Poly;
+    use crate::vtfhe::crypto::rng::test_rng;
 
     use plonky2::field::types::Sample;
     use plonky2::iop::witness::PartialWitness;
@@ -142,11 +143,12 @@ mod tests {
         glwe_poly.register(&mut builder);
         glev_ct.register(&mut builder);
 
-        let s = Glwe::<F, D, N, K>::key_gen();
-        let m = F::rand();
-        let a = Poly::<F, D, N>::rand();
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
+        let m = F::sample(rng);
+        let a = Poly::<F, D, N>::rand(rng);
 
-        let ct = Glev::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &Poly::constant(&m), 0f64);
+        let ct = Glev::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &Poly::constant(&m), 0f64, rng);
         glev_ct.assign(&mut pw, &ct.ntt_forward());
         glwe_poly.assign(&mut pw, &a);
 
@@ -161,6 +163,7 @@ mod tests {
         let start = GlwePoly::<N>::num_targets() + GlevCt::<N, K, ELL>::num_targets();
         let out_glwe_flat = &proof.public_inputs[start..start + GlweCt::<N, K>::num_targets()];
         let out_glwe = Glwe::<F, D, N, K>::from_slice(out_glwe_flat);
+        assert_eq!(out_glwe, ct.ntt_forward().mul::<LOGB>(&a));
         let out_ct = out_glwe.ntt_backward().sample_extract();
         let m0 = decrypt::<F, D, { N * K }>(&Glwe::<F, D, N, K>::flatten_key(&s), &out_ct);
         assert_eq!(m * a.coeffs[0], m0);
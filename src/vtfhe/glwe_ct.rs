@@ -110,7 +110,78 @@ impl<const N: usize, const K: usize> GlweCt<N, K> {
         }
     }
 
+    // the LWE ciphertext of the constant coefficient under the flattened GLWE key, i.e. of
+    // dimension (K - 1) * N, see `Glwe::sample_extract`
+    pub fn sample_extract<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+    ) -> Vec<Target> {
+        self.partial_sample_extract(cb, (K - 1) * N)
+    }
+
+    // the sample extraction under a partial GLWE key whose first nz coefficients are the LWE
+    // key (and the others zero), i.e. an LWE ciphertext of dimension nz, see
+    // `Glwe::partial_sample_extract`
+    pub fn partial_sample_extract<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        cb: &mut CircuitBuilder<F, D>,
+        nz: usize,
+    ) -> Vec<Target> {
+        let mut lwe: Vec<Target> = (0..nz)
+            .map(|j| match j % N {
+                0 => self.polys[j / N].coeffs[0],
+                i => cb.neg(self.polys[j / N].coeffs[N - i]),
+            })
+            .collect();
+        lwe.push(self.polys[K - 1].coeffs[0]);
+        lwe
+    }
+
     pub fn num_targets() -> usize {
         K * N
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::poly::Poly;
+    use crate::vtfhe::crypto::rng::test_rng;
+
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    #[test]
+    fn test_sample_extract() {
+        const K: usize = 3;
+        const D: usize = 2;
+        let nz = N + 7;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let mut pw = PartialWitness::new();
+
+        let glwe = GlweCt::<N, K>::new_from_builder(&mut builder);
+        let lwe = glwe.sample_extract(&mut builder);
+        let partial_lwe = glwe.partial_sample_extract(&mut builder, nz);
+        builder.register_public_inputs(&lwe);
+        builder.register_public_inputs(&partial_lwe);
+
+        let rng = &mut test_rng();
+        let glwe_val = Glwe::<F, D, N, K> {
+            polys: from_fn(|_| Poly::rand(rng)),
+        };
+        glwe.assign(&mut pw, &glwe_val);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof.clone()).unwrap();
+
+        let (lwe_out, partial_lwe_out) = proof.public_inputs.split_at((K - 1) * N + 1);
+        assert_eq!(lwe_out, glwe_val.sample_extract());
+        assert_eq!(partial_lwe_out, glwe_val.partial_sample_extract(nz));
+    }
+}
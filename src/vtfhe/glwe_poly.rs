@@ -120,7 +120,7 @@ impl<const N: usize> GlwePoly<N> {
         cb: &mut CircuitBuilder<F, D>,
     ) -> GlwePoly<N> {
         GlwePoly {
-            coeffs: ntt_backward(cb, &self.coeffs.to_vec()).try_into().unwrap(),
+            coeffs: ntt_backward::<F, D, N>(cb, &self.coeffs).try_into().unwrap(),
         }
     }
 
@@ -177,6 +177,7 @@ impl<const N: usize> GlwePoly<N> {
 
 #[cfg(test)]
 mod tests {
+    use crate::vtfhe::crypto::poly::decompose as native_decompose;
     use crate::vtfhe::glwe_poly::GlwePoly;
     use crate::{ntt::params::N, vtfhe::glwe_poly::decompose};
     use plonky2::field::types::Field;
@@ -275,6 +276,7 @@ mod tests {
             out, base, input
         );
         println!("{input} decomposed is {:?}", out);
+        assert_eq!(out, native_decompose::<F, D, LOGB>(input, num_limbs));
 
         let _ = data.verify(proof).unwrap();
     }
@@ -0,0 +1,162 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::GenericConfig;
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use std::iter::once;
+
+use super::{glwe_pbs_step, PbsCircuitOptions, PublicInputRanges};
+use crate::vtfhe::crypto::glwe::Glwe;
+use crate::vtfhe::ggsw_ct::GgswCt;
+use crate::vtfhe::glwe_ct::GlweCt;
+use crate::vtfhe::glwe_select;
+
+// the mask element and the accumulator of each other ciphertext of a batch, which go through
+// the same steps as the first one, with the same GGSW
+pub(super) struct BatchTargets<const N: usize, const K: usize> {
+    pub(super) lwe_cts: Vec<Target>,
+    accs_in: Vec<GlweCt<N, K>>,
+}
+
+impl<const N: usize, const K: usize> BatchTargets<N, K> {
+    pub(super) fn new<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        batch_size: usize,
+    ) -> Self {
+        let (lwe_cts, accs_in) = (1..batch_size)
+            .map(|_| (builder.add_virtual_target(), GlweCt::new_from_builder(builder)))
+            .unzip();
+        BatchTargets { lwe_cts, accs_in }
+    }
+}
+
+// the step of the PBS of each other ciphertext of a batch; returns their accumulators after it
+pub(super) fn batch_pbs_steps<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const LOGB: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    batch: &BatchTargets<N, K>,
+    ggsw: &GgswCt<N, K, ELL>,
+    first_step: BoolTarget,
+    last_step: BoolTarget,
+) -> Vec<GlweCt<N, K>> {
+    batch
+        .lwe_cts
+        .iter()
+        .zip(&batch.accs_in)
+        .map(|(&batch_mask_element, batch_acc_in)| {
+            glwe_pbs_step::<F, D, LOGB, N, K, ELL>(
+                builder,
+                ggsw,
+                batch_acc_in,
+                batch_mask_element,
+                first_step,
+                last_step,
+            )
+        })
+        .collect()
+}
+
+// connects the accumulators of the other ciphertexts of a batch to those of the previous proof,
+// or to the test vector in the base case
+pub(super) fn connect_batch<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    batch: &BatchTargets<N, K>,
+    inner_cyclic_batch_accs: &[Target],
+    acc_init: &GlweCt<N, K>,
+    condition: BoolTarget,
+) {
+    for (batch_acc_in, inner_cyclic_batch_acc) in batch
+        .accs_in
+        .iter()
+        .zip(inner_cyclic_batch_accs.chunks(GlweCt::<N, K>::num_targets()))
+    {
+        let inner_cyclic_batch_acc = GlweCt::new_from_targets(inner_cyclic_batch_acc);
+        let actual_batch_acc_in = glwe_select(builder, condition, &inner_cyclic_batch_acc, acc_init);
+        for (left, right) in batch_acc_in
+            .flatten()
+            .into_iter()
+            .zip(actual_batch_acc_in.flatten())
+        {
+            builder.connect(left, right);
+        }
+    }
+}
+
+// the output ciphertexts of a batch in the public inputs of its proof: that of the first
+// ciphertext in the place of the output of a single PBS, followed by the others
+pub(super) fn batch_outputs<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+>(
+    options: PbsCircuitOptions,
+    proof: &ProofWithPublicInputs<F, C, D>,
+) -> Vec<Glwe<F, D, N, K>> {
+    let ranges = PublicInputRanges::new::<n, N, K>(options);
+    let first_out_ct = &proof.public_inputs[ranges.latest_acc.0..ranges.latest_acc.1];
+    let other_out_cts = ranges
+        .batch_accs
+        .map_or(&[][..], |range| &proof.public_inputs[range.0..range.1]);
+    once(first_out_ct)
+        .chain(other_out_cts.chunks(GlweCt::<N, K>::num_targets()))
+        .map(Glwe::from_slice)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{test_fixture, TestFixture, C, D, ELL, F, K, LOGB};
+    use super::super::{verified_pbs, verify_pbs, PbsCircuitOptions, PbsInputs, PbsStatement};
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::pbs;
+    use crate::vtfhe::crypto::rng::test_rng;
+
+    use plonky2::field::types::Field;
+
+    #[test]
+    fn test_ivc_pbs_batch() {
+        const n: usize = 1;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_lwe, bsk, ksk, testv, delta, .. } = test_fixture::<n, N>(rng);
+        let cts: Vec<Vec<F>> = (0..2)
+            .map(|m| s_lwe.encrypt(&(delta * F::from_canonical_usize(m)), 0f64, rng))
+            .collect();
+
+        let options = PbsCircuitOptions {
+            batch_size: cts.len(),
+            ..Default::default()
+        };
+        let batch: Vec<&[F]> = cts.iter().map(Vec::as_slice).collect();
+        let inputs = PbsInputs::new_batch(batch, &testv, bsk.ggsws(), ksk.ggsw());
+        let (outputs, proof, cd) =
+            verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(options, &inputs, None, None, None).unwrap();
+        assert_eq!(outputs.glwes.len(), cts.len());
+        for (out_ct, ct) in outputs.glwes.iter().zip(&cts) {
+            assert_eq!(*out_ct, pbs::<F, D, n, N, K, ELL, LOGB>(ct, &testv, bsk.ggsws(), ksk.ggsw()));
+        }
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            options,
+            &outputs,
+            &PbsStatement::new::<n, ELL>(&inputs).digest(),
+            &proof,
+            &cd.verifier_data(),
+        )
+        .unwrap();
+    }
+}
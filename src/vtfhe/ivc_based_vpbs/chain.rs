@@ -0,0 +1,196 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use std::iter::once;
+
+use crate::vec_arithmetic::vec_select;
+use crate::vtfhe::glwe_ct::GlweCt;
+
+// the state of a chain of PBSs passed from step to step: the position of the step in its PBS,
+// the number of PBSs started so far, and the elements of the input LWE ciphertext of the
+// current PBS that are left to consume
+pub(super) struct ChainTargets {
+    step_in: Target,
+    pbs_count_in: Target,
+    pending_ct_in: Vec<Target>,
+}
+
+// the public inputs of the state of a chain of PBSs, see `ChainTargets`
+pub(super) struct ChainRanges {
+    pub(super) step: usize,
+    pub(super) pbs_count: usize,
+    pub(super) pending_ct: (usize, usize),
+}
+
+impl ChainRanges {
+    // the ranges of the state, published from the public input `start` on
+    pub(super) fn new<const n: usize>(start: usize) -> Self {
+        ChainRanges {
+            step: start,
+            pbs_count: start + 1,
+            pending_ct: (start + 2, start + n + 3),
+        }
+    }
+}
+
+// the state of a chain of PBSs after a step, which is published by `register`
+pub(super) struct ChainState {
+    targets: ChainTargets,
+    // whether the step is part of the first PBS of the chain
+    pub(super) first_pbs: BoolTarget,
+    step: Target,
+    pbs_count: Target,
+    pending_ct_out: Vec<Target>,
+}
+
+impl ChainState {
+    pub(super) fn register<F: RichField + Extendable<D>, const D: usize>(
+        self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> ChainTargets {
+        builder.register_public_input(self.step);
+        builder.register_public_input(self.pbs_count);
+        builder.register_public_inputs(&self.pending_ct_out);
+        self.targets
+    }
+}
+
+// the position of a step in its PBS, given that of the previous step, which starts over after
+// the key switch of each PBS
+pub(super) fn chain_step<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    step_in: Target,
+    key_switch_target: Target,
+) -> Target {
+    let one = builder.one();
+    let zero = builder.zero();
+    let pbs_done = builder.is_equal(step_in, key_switch_target);
+    let step_in = builder.select(pbs_done, zero, step_in);
+    builder.add(step_in, one)
+}
+
+// Each PBS of a chain starts from the test vector, and bootstraps the sample extraction of the
+// output of the previous PBS (if any): its elements are set aside in the first step, and each
+// step consumes one of them as its mask element.
+pub(super) fn chain_pbs<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    step_in: Target,
+    step: Target,
+    first_step: BoolTarget,
+    prev_acc: &GlweCt<N, K>,
+    mask_element: Target,
+) -> ChainState {
+    let one = builder.one();
+    let zero = builder.zero();
+    let pbs_count_in = builder.add_virtual_target();
+    let pbs_count = builder.add(pbs_count_in, first_step.target);
+    let first_pbs = builder.is_equal(pbs_count, one);
+    let extracted = prev_acc.partial_sample_extract(builder, n);
+    let prev_out_ct: Vec<Target> = once(extracted[n])
+        .chain(extracted[..n].iter().copied())
+        .collect();
+    let pending_ct_in = builder.add_virtual_targets(n + 1);
+    let pending_ct = vec_select(builder, first_step, &prev_out_ct, &pending_ct_in);
+    let expected_mask = builder.select(first_pbs, mask_element, pending_ct[0]);
+    builder.connect(mask_element, expected_mask);
+    let pending_ct_out: Vec<Target> = pending_ct[1..].iter().copied().chain(once(zero)).collect();
+
+    ChainState {
+        targets: ChainTargets {
+            step_in,
+            pbs_count_in,
+            pending_ct_in,
+        },
+        first_pbs,
+        step,
+        pbs_count,
+        pending_ct_out,
+    }
+}
+
+// connects the state of the chain to that of the previous proof, or to step 0 of the first PBS
+// in the base case
+pub(super) fn connect_chain<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    targets: &ChainTargets,
+    ranges: &ChainRanges,
+    inner_cyclic_pis: &[Target],
+    condition: BoolTarget,
+) {
+    let zero = builder.zero();
+    let actual_step_in = builder.select(condition, inner_cyclic_pis[ranges.step], zero);
+    builder.connect(targets.step_in, actual_step_in);
+    let actual_pbs_count_in = builder.select(condition, inner_cyclic_pis[ranges.pbs_count], zero);
+    builder.connect(targets.pbs_count_in, actual_pbs_count_in);
+    let inner_cyclic_pending_ct = &inner_cyclic_pis[ranges.pending_ct.0..ranges.pending_ct.1];
+    for (&left, &right) in targets.pending_ct_in.iter().zip(inner_cyclic_pending_ct) {
+        let actual_right = builder.select(condition, right, zero);
+        builder.connect(left, actual_right);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::array::from_fn;
+
+    use super::super::tests::{test_fixture, TestFixture, C, D, ELL, F, K, LOGB};
+    use super::super::{verified_pbs, verify_pbs, PbsCircuitOptions, PbsInputs, PbsStatement};
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::poly::Poly;
+    use crate::vtfhe::crypto::rng::test_rng;
+    use crate::vtfhe::crypto::{pbs, pbs_chain};
+
+    use plonky2::field::types::Field;
+    use rand::Rng;
+
+    #[test]
+    fn test_ivc_pbs_chain() {
+        const n: usize = 1;
+        const NUM_PBS: usize = 2;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_to, s_lwe, bsk, ksk, delta, .. } = test_fixture::<n, N>(rng);
+        let testv = Poly::<F, D, N> {
+            coeffs: from_fn(|i| delta * F::from_canonical_usize(i)),
+        };
+        let m = F::from_canonical_u64(rng.gen::<u64>() % (N as u64));
+        let ct = s_lwe.encrypt(&(delta * m), 0f64, rng);
+
+        let options = PbsCircuitOptions {
+            chain: true,
+            ..Default::default()
+        };
+        let inputs = PbsInputs::new(&ct, &testv, bsk.ggsws(), ksk.ggsw()).chain(NUM_PBS);
+        let (outputs, proof, cd) =
+            verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(options, &inputs, None, None, None).unwrap();
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            options,
+            &outputs,
+            &PbsStatement::new::<n, ELL>(&inputs).digest(),
+            &proof,
+            &cd.verifier_data(),
+        )
+        .unwrap();
+        let out_ct = outputs.into_glwe().unwrap();
+        assert_eq!(
+            out_ct,
+            pbs_chain::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw(), NUM_PBS)
+        );
+
+        // the second PBS bootstraps the output of the first one under the LWE key
+        let first_out_ct = pbs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        let first_out_lwe = first_out_ct.partial_sample_extract(n);
+        assert_eq!(s_lwe.decrypt(&first_out_lwe), s_to.decrypt(&first_out_ct).coeffs[0]);
+        assert_eq!(
+            out_ct,
+            pbs::<F, D, n, N, K, ELL, LOGB>(&first_out_lwe, &testv, bsk.ggsws(), ksk.ggsw())
+        );
+    }
+}
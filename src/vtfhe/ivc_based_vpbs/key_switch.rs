@@ -0,0 +1,255 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOutTarget, RichField, NUM_HASH_OUT_ELTS};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use std::iter::{once, repeat_n};
+
+use super::{ggsw_digest, hash_select};
+use crate::vec_arithmetic::vec_select;
+use crate::vtfhe::crypto::ggsw::Ggsw;
+use crate::vtfhe::crypto::keys::LweKeySwitchKey;
+use crate::vtfhe::ggsw_ct::GgswCt;
+use crate::vtfhe::glwe_ct::GlweCt;
+use crate::vtfhe::lev_ct::LevCt;
+use crate::vtfhe::{glwe_select, key_switch_chunk};
+
+// the state of the LWE-to-LWE key switch passed from step to step: the LWE accumulator, whether
+// the key switch has started, and the hash of the BSK, which is set aside while the BSK hash
+// chain absorbs the chunks of the KSK
+pub(super) struct LweKeySwitchTargets {
+    lwe_acc_in: Vec<Target>,
+    key_switching_in: BoolTarget,
+    bsk_hash_in: HashOutTarget,
+}
+
+// the public inputs of the state of the LWE-to-LWE key switch, see `LweKeySwitchTargets`
+pub(super) struct LweKeySwitchRanges {
+    pub(super) lwe_acc: (usize, usize),
+    pub(super) key_switching: usize,
+    pub(super) hash_bsk: (usize, usize),
+}
+
+impl LweKeySwitchRanges {
+    // the ranges of the state, published from the public input `start` on
+    pub(super) fn new<const n: usize>(start: usize) -> Self {
+        let lwe_acc = (start, start + n + 1);
+        let key_switching = lwe_acc.1;
+        let hash_bsk = (key_switching + 1, key_switching + 1 + NUM_HASH_OUT_ELTS);
+        LweKeySwitchRanges {
+            lwe_acc,
+            key_switching,
+            hash_bsk,
+        }
+    }
+}
+
+// the state of the LWE-to-LWE key switch after a step, which is published by `register`
+pub(super) struct LweKeySwitchState {
+    targets: LweKeySwitchTargets,
+    lwe_acc_out: Vec<Target>,
+    key_switching: BoolTarget,
+    bsk_hash_out: HashOutTarget,
+}
+
+impl LweKeySwitchState {
+    pub(super) fn register<F: RichField + Extendable<D>, const D: usize>(
+        self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> LweKeySwitchTargets {
+        builder.register_public_inputs(&self.lwe_acc_out);
+        builder.register_public_input(self.key_switching.target);
+        builder.register_public_inputs(&self.bsk_hash_out.elements);
+        self.targets
+    }
+}
+
+// A step of the PBS with `KeySwitchMode::Lwe`, given the accumulator after the blind rotation
+// step. Each step of the key switch reads the Levs of a chunk of the KSK from the GGSW, switches
+// the first `CHUNK_SIZE` coefficients of the mask with them, and shifts these coefficients out
+// of the accumulator; the first step starts from the body. Returns the accumulator and the BSK
+// hash chain after the step, the hash of the BSK (set aside once the key switch has started) and
+// the state of the key switch.
+pub(super) fn lwe_key_switch_step<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const LOGB: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    ggsw: &GgswCt<N, K, ELL>,
+    ggsw_digest: HashOutTarget,
+    acc_in: &GlweCt<N, K>,
+    blind_rotation_out: &GlweCt<N, K>,
+    first_key_switch: BoolTarget,
+    bsk_hash_in: HashOutTarget,
+) -> (GlweCt<N, K>, HashOutTarget, HashOutTarget, LweKeySwitchState) {
+    let zero = builder.zero();
+    let key_switching_in = builder.add_virtual_bool_target_safe();
+    let key_switching = builder.or(key_switching_in, first_key_switch);
+
+    let chunk_size = LweKeySwitchKey::<F, D, n, N, K, ELL>::CHUNK_SIZE;
+    let lev_len = LevCt::<n, ELL>::num_targets();
+    let ksk_chunk: Vec<LevCt<n, ELL>> = ggsw.flatten()[..chunk_size * lev_len]
+        .chunks(lev_len)
+        .map(LevCt::new_from_targets)
+        .collect();
+    let acc_in = acc_in.flatten();
+    let mask_len = (K - 1) * N;
+    let lwe_acc_in = builder.add_virtual_targets(n + 1);
+    let lwe_init: Vec<Target> = repeat_n(zero, n).chain(once(acc_in[mask_len])).collect();
+    let lwe_start = vec_select(builder, first_key_switch, &lwe_init, &lwe_acc_in);
+    let lwe_switched =
+        key_switch_chunk::<F, D, LOGB, n, ELL>(builder, &lwe_start, &acc_in[..chunk_size], &ksk_chunk);
+    let lwe_acc_out = vec_select(builder, key_switching, &lwe_switched, &lwe_acc_in);
+    let acc_shifted: Vec<Target> = acc_in[chunk_size..mask_len]
+        .iter()
+        .copied()
+        .chain(repeat_n(zero, chunk_size))
+        .chain(acc_in[mask_len..].iter().copied())
+        .collect();
+    let acc_out = glwe_select(
+        builder,
+        key_switching,
+        &GlweCt::new_from_targets(&acc_shifted),
+        blind_rotation_out,
+    );
+
+    // the BSK hash chain is restarted for the chunks of the KSK, so that its hash is the chain
+    // over them, and the hash of the BSK is set aside
+    let bsk_snapshot_in = builder.add_virtual_hash();
+    let zero_hash = HashOutTarget {
+        elements: [zero; NUM_HASH_OUT_ELTS],
+    };
+    let chain_in = hash_select(builder, first_key_switch, zero_hash, bsk_hash_in);
+    let bsk_hash_out = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+        chain_in.elements.into_iter().chain(ggsw_digest.elements).collect(),
+    );
+    let bsk_snapshot_out = hash_select(builder, first_key_switch, bsk_hash_in, bsk_snapshot_in);
+
+    let state = LweKeySwitchState {
+        targets: LweKeySwitchTargets {
+            lwe_acc_in,
+            key_switching_in,
+            bsk_hash_in: bsk_snapshot_in,
+        },
+        lwe_acc_out,
+        key_switching,
+        bsk_hash_out: bsk_snapshot_out,
+    };
+    (acc_out, bsk_hash_out, bsk_snapshot_out, state)
+}
+
+// connects the state of the key switch to that of the previous proof, or to zero (and not key
+// switching) in the base case
+pub(super) fn connect_lwe_key_switch<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    targets: &LweKeySwitchTargets,
+    ranges: &LweKeySwitchRanges,
+    inner_cyclic_pis: &[Target],
+    condition: BoolTarget,
+) {
+    let zero = builder.zero();
+    let inner_cyclic_lwe_acc = &inner_cyclic_pis[ranges.lwe_acc.0..ranges.lwe_acc.1];
+    for (&left, &right) in targets.lwe_acc_in.iter().zip(inner_cyclic_lwe_acc) {
+        let actual_right = builder.select(condition, right, zero);
+        builder.connect(left, actual_right);
+    }
+    let actual_key_switching_in =
+        builder.select(condition, inner_cyclic_pis[ranges.key_switching], zero);
+    builder.connect(targets.key_switching_in.target, actual_key_switching_in);
+    let inner_cyclic_bsk_hash =
+        HashOutTarget::try_from(&inner_cyclic_pis[ranges.hash_bsk.0..ranges.hash_bsk.1]).unwrap();
+    let actual_bsk_hash_in = inner_cyclic_bsk_hash
+        .elements
+        .map(|t| builder.select(condition, t, zero));
+    builder.connect_hashes(targets.bsk_hash_in, HashOutTarget::from(actual_bsk_hash_in));
+}
+
+// the digests of the GGSWs holding the chunks of an `LweKeySwitchKey`, which are absorbed into
+// a hash chain of their own (see `lwe_key_switch_step`)
+pub(super) fn hash_ksk_data<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+>(
+    ksk_ggsws: &[Ggsw<F, D, N, K, ELL>],
+) -> Vec<Vec<F>> {
+    ksk_ggsws
+        .iter()
+        .map(|ggsw| ggsw_digest(ggsw).elements.to_vec())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::array::from_fn;
+
+    use super::super::tests::{check_rotation, C, D, ELL, F, K, LOGB};
+    use super::super::{
+        pbs_lwe_public_inputs, verified_pbs, verify_pbs, KeySwitchMode, PbsCircuitOptions,
+        PbsInputs, PbsStatement,
+    };
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::keys::{BootstrapKey, GlweSecretKey, LweKeySwitchKey, LweSecretKey};
+    use crate::vtfhe::crypto::poly::Poly;
+    use crate::vtfhe::crypto::rng::test_rng;
+    use crate::vtfhe::crypto::{blind_rotate, pbs_lwe};
+
+    use plonky2::field::types::Field;
+    use plonky2::util::log2_ceil;
+    use rand::Rng;
+
+    #[test]
+    fn test_ivc_pbs_lwe() {
+        const n: usize = 4;
+
+        let rng = &mut test_rng();
+        let s_lwe = LweSecretKey::<F, D, n>::generate(rng);
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, 0f64, rng);
+        let ksk = LweKeySwitchKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_lwe, 0f64, rng);
+        // the key switch is split over several steps
+        const { assert!(LweKeySwitchKey::<F, D, n, N, K, ELL>::NUM_CHUNKS > 1) };
+
+        let testv = Poly::<F, D, N> {
+            coeffs: from_fn(F::from_canonical_usize),
+        };
+        let delta = F::from_noncanonical_biguint(F::order() >> log2_ceil(2 * N));
+        let m = F::from_canonical_u64(rng.gen::<u64>() % (N as u64));
+        let ct = s_lwe.encrypt(&(delta * m), 0f64, rng);
+
+        let options = PbsCircuitOptions {
+            key_switch: KeySwitchMode::Lwe,
+            ..Default::default()
+        };
+        let inputs = PbsInputs::new_lwe(&ct, &testv, bsk.ggsws(), &ksk);
+        let (outputs, proof, cd) =
+            verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(options, &inputs, None, None, None).unwrap();
+        assert!(outputs.glwes.is_empty());
+        let out_ct = outputs.lwe.as_ref().unwrap();
+        assert_eq!(*out_ct, pbs_lwe::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), &ksk));
+        let expected_pis =
+            pbs_lwe_public_inputs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), &ksk);
+        assert_eq!(expected_pis, proof.public_inputs[..expected_pis.len()]);
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            options,
+            &outputs,
+            &PbsStatement::new::<n, ELL>(&inputs).digest(),
+            &proof,
+            &cd.verifier_data(),
+        )
+        .unwrap();
+
+        // without noise, the output decrypts under the LWE key to the constant coefficient of
+        // the rotated test vector
+        let acc = blind_rotate::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws());
+        check_rotation(&testv, &s_glwe.decrypt(&acc), &(-delta * m));
+        assert_eq!(s_lwe.decrypt(out_ct), s_glwe.decrypt(&acc).coeffs[0]);
+    }
+}
@@ -0,0 +1,2258 @@
+use crate::vtfhe::crypto::lwe::mod_switch_ct;
+use crate::vtfhe::{glwe_select, rotate_glwe};
+use anyhow::{anyhow, ensure, Context, Result};
+use log::{info, Level};
+use plonky2::field::extension::Extendable;
+use plonky2::gates::noop::NoopGate;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField, NUM_HASH_OUT_ELTS};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{
+    CircuitConfig, CircuitData, CommonCircuitData, VerifierCircuitData, VerifierCircuitTarget,
+};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+use plonky2::plonk::prover::prove;
+use plonky2::recursion::cyclic_recursion::check_cyclic_proof_verifier_data;
+use plonky2::recursion::dummy_circuit::cyclic_base_proof;
+use plonky2::timed;
+use plonky2::util::serialization::{
+    Buffer, DefaultGateSerializer, DefaultGeneratorSerializer, IoResult, Read, Write,
+};
+use plonky2::util::timing::TimingTree;
+use serde::{Deserialize, Serialize};
+use std::array::from_fn;
+use std::fs;
+use std::iter::{once, repeat_n};
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use super::crypto::ggsw::Ggsw;
+use super::crypto::glwe::Glwe;
+use super::crypto::keys::{GlweSecretKey, LweKeySwitchKey, LweSecretKey};
+use super::crypto::poly::Poly;
+use super::crypto::{blind_rotate, key_switch, pbs};
+use super::ggsw_ct::GgswCt;
+use super::glwe_ct::GlweCt;
+use super::progress::{resident_memory, ProgressBar, ProgressObserver, StepProgress};
+
+mod batch;
+mod chain;
+mod key_switch;
+mod sample_extract;
+
+use batch::{batch_outputs, batch_pbs_steps, connect_batch, BatchTargets};
+use chain::{chain_pbs, chain_step, connect_chain, ChainRanges, ChainTargets};
+use key_switch::{
+    connect_lwe_key_switch, hash_ksk_data, lwe_key_switch_step, LweKeySwitchRanges,
+    LweKeySwitchTargets,
+};
+use sample_extract::register_sample_extraction;
+
+// How the PBS switches the blind-rotated accumulator to the output key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeySwitchMode {
+    // an external product with the GGSW of a `KeySwitchKey`, in a single step; the output is a
+    // GLWE ciphertext under a partial GLWE key whose first n coefficients are the LWE key
+    #[default]
+    Glwe,
+    // the sample extraction and LWE-to-LWE key switch with an `LweKeySwitchKey`, which takes
+    // `LweKeySwitchKey::NUM_CHUNKS` steps; the output is an LWE ciphertext of dimension n under
+    // an independent LWE key, so it can be bootstrapped again
+    Lwe,
+}
+
+// The options of the cyclic circuit of a `PbsProver`, which determine its public inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PbsCircuitOptions {
+    pub key_switch: KeySwitchMode,
+    // also publish the sample extraction of the output GLWE ciphertext, an LWE ciphertext of
+    // dimension n under the partial output key (see `PbsOutputs::lwe`); the output of
+    // `KeySwitchMode::Lwe` is an LWE ciphertext already
+    pub extract_lwe: bool,
+    // prove a chain of PBSs with the same keys and test vector, each of which after the first
+    // bootstraps the sample extraction of the output of the previous one (see
+    // `PbsInputs::chain`); only with `KeySwitchMode::Glwe`
+    pub chain: bool,
+    // the number of ciphertexts bootstrapped together with the same keys and test vector (see
+    // `PbsInputs::new_batch`): each step applies its GGSW to the accumulators of all of them;
+    // only with `KeySwitchMode::Glwe`, without chains or sample extraction
+    pub batch_size: usize,
+}
+
+impl Default for PbsCircuitOptions {
+    fn default() -> Self {
+        PbsCircuitOptions {
+            key_switch: KeySwitchMode::default(),
+            extract_lwe: false,
+            chain: false,
+            batch_size: 1,
+        }
+    }
+}
+
+impl PbsCircuitOptions {
+    fn to_params(self) -> [usize; 4] {
+        [
+            self.key_switch as usize,
+            self.extract_lwe as usize,
+            self.chain as usize,
+            self.batch_size,
+        ]
+    }
+
+    fn from_params(params: &[usize]) -> Result<Self> {
+        let key_switch = match params[0] {
+            0 => KeySwitchMode::Glwe,
+            1 => KeySwitchMode::Lwe,
+            mode => return Err(anyhow!("unknown key switch mode {mode} of the PBS circuit")),
+        };
+        ensure!(params[3] > 0, "the PBS circuit has an empty batch");
+        Ok(PbsCircuitOptions {
+            key_switch,
+            extract_lwe: params[1] != 0,
+            chain: params[2] != 0,
+            batch_size: params[3],
+        })
+    }
+}
+
+// Generates `CommonCircuitData` usable for recursion: that of a recursive verifier, padded to
+// a degree of at least 2^degree_bits.
+fn common_data_for_recursion<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    degree_bits: usize,
+) -> CommonCircuitData<F, D>
+where
+    C::Hasher: AlgebraicHasher<F>,
+{
+    let config = CircuitConfig::standard_recursion_config();
+    let builder = CircuitBuilder::<F, D>::new(config);
+    let data = builder.build::<C>();
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+    let proof = builder.add_virtual_proof_with_pis(&data.common);
+    let verifier_data = builder.add_virtual_verifier_data(data.common.config.fri_config.cap_height);
+    builder.verify_proof::<C>(&proof, &verifier_data, &data.common);
+    let data = builder.build::<C>();
+
+    let config = CircuitConfig::standard_recursion_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+    let proof = builder.add_virtual_proof_with_pis(&data.common);
+    let verifier_data = builder.add_virtual_verifier_data(data.common.config.fri_config.cap_height);
+    builder.verify_proof::<C>(&proof, &verifier_data, &data.common);
+
+    // building adds at least the public input gate, so more than half of the gates of a
+    // circuit of degree 2^degree_bits suffice
+    while degree_bits > 0 && builder.num_gates() < 1 << (degree_bits - 1) {
+        builder.add_gate(NoopGate, vec![]);
+    }
+    builder.build::<C>().common
+}
+
+// Builds a cyclic circuit with `build`, which is given the common data of the proofs the
+// circuit verifies. These are proofs of the circuit itself, so its common data has to be known
+// before it is built. Starting from the smallest recursive verifier, the verifier is padded to
+// the degree of the cyclic circuit built with it, until the two agree. This usually takes two
+// builds, the first of which only determines the degree.
+pub(super) fn build_cyclic_circuit_data<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+    T,
+>(
+    build: impl Fn(CommonCircuitData<F, D>) -> (CircuitBuilder<F, D>, T),
+) -> (CircuitData<F, C, D>, T)
+where
+    C::Hasher: AlgebraicHasher<F>,
+{
+    let mut degree_bits = 0;
+    loop {
+        let common_data = common_data_for_recursion::<F, C, D>(degree_bits);
+        let verifier_degree_bits = common_data.degree_bits();
+        let (builder, targets) = build(common_data);
+        let (circuit_data, success) = builder.try_build_with_options::<C>(true);
+        if success {
+            info!("cyclic circuit of degree 2^{verifier_degree_bits}");
+            return (circuit_data, targets);
+        }
+        // only the degree can be fixed by padding
+        assert!(
+            circuit_data.common.degree_bits() > verifier_degree_bits,
+            "the cyclic circuit does not match the recursive verifier of degree 2^{verifier_degree_bits}"
+        );
+        degree_bits = circuit_data.common.degree_bits();
+        info!("padding the recursive verifier to degree 2^{degree_bits}");
+    }
+}
+
+fn hash_output<F: RichField>(hash_data: &[Vec<F>]) -> HashOut<F> {
+    let mut hash = HashOut::ZERO;
+
+    for data in hash_data {
+        let data_in: Vec<F> = hash
+            .elements
+            .into_iter()
+            .chain(data.into_iter().map(|&d| d))
+            .collect();
+        hash = PoseidonHash::hash_no_pad(&data_in);
+    }
+    hash
+}
+
+// the digests of the GGSWs absorbed into the BSK hash chain before the key switch, one per step
+fn hash_bsk_data<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+>(
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+) -> Vec<Vec<F>> {
+    once(ggsw_digest(&Ggsw::<F, D, N, K, ELL>::dummy_ct()))
+        .chain(bsk.iter().map(ggsw_digest))
+        .map(|digest| digest.elements.to_vec())
+        .collect()
+}
+
+pub(super) fn ggsw_digest<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+>(
+    ggsw: &Ggsw<F, D, N, K, ELL>,
+) -> HashOut<F> {
+    PoseidonHash::hash_no_pad(&ggsw.flatten())
+}
+
+// the LWE element consumed by each step: the body, the mask elements, and zero for each step
+// of the key switch
+pub(super) fn step_masks<F: RichField, const n: usize>(ct: &[F], num_key_switch_steps: usize) -> Vec<F> {
+    once(ct[n])
+        .chain(ct[..n].iter().copied())
+        .chain(repeat_n(F::ZERO, num_key_switch_steps))
+        .collect()
+}
+
+// the LWE elements absorbed into the LWE hash chain in each step, one of each ciphertext of a
+// batch
+fn hash_lwe_data<F: RichField, const n: usize>(
+    cts: &[&[F]],
+    num_key_switch_steps: usize,
+) -> Vec<Vec<F>> {
+    let masks: Vec<Vec<F>> = cts
+        .iter()
+        .map(|ct| step_masks::<F, n>(ct, num_key_switch_steps))
+        .collect();
+    (0..n + 1 + num_key_switch_steps)
+        .map(|step| masks.iter().map(|ct_masks| ct_masks[step]).collect())
+        .collect()
+}
+
+// the GGSWs consumed by the steps of the key switch of a PBS
+enum KeySwitchGgsws<
+    'a,
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+> {
+    // the GGSW of a `KeySwitchKey`, in a single step
+    Glwe(&'a Ggsw<F, D, N, K, ELL>),
+    // the chunks of an `LweKeySwitchKey`, see `LweKeySwitchKey::step_ggsws`
+    Lwe(Vec<Ggsw<F, D, N, K, ELL>>),
+}
+
+// The PBSs proven by `verified_pbs`: the PBS of each ciphertext of `cts` (a single one unless
+// the circuit is built for a batch) with the test vector `testv`, the bootstrapping key `bsk`
+// and a key switching key, which also determines the `KeySwitchMode`. With a circuit built
+// with `PbsCircuitOptions::chain`, each of the `num_pbs` PBSs of the chain after the first
+// bootstraps the sample extraction of the output of the previous one.
+pub struct PbsInputs<
+    'a,
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+> {
+    pub cts: Vec<&'a [F]>,
+    pub testv: &'a Poly<F, D, N>,
+    pub bsk: &'a [Ggsw<F, D, N, K, ELL>],
+    ksk: KeySwitchGgsws<'a, F, D, N, K, ELL>,
+    pub num_pbs: usize,
+}
+
+impl<
+        'a,
+        F: RichField + Extendable<D>,
+        const D: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+    > PbsInputs<'a, F, D, N, K, ELL>
+{
+    // the PBS of `ct` with the GLWE key switch
+    pub fn new(
+        ct: &'a [F],
+        testv: &'a Poly<F, D, N>,
+        bsk: &'a [Ggsw<F, D, N, K, ELL>],
+        ksk: &'a Ggsw<F, D, N, K, ELL>,
+    ) -> Self {
+        Self::new_batch(vec![ct], testv, bsk, ksk)
+    }
+
+    // the PBS of `ct` with the LWE-to-LWE key switch, whose output is an LWE ciphertext of
+    // dimension n under the output key of `ksk`, which can be bootstrapped again with `bsk`
+    pub fn new_lwe<const n: usize>(
+        ct: &'a [F],
+        testv: &'a Poly<F, D, N>,
+        bsk: &'a [Ggsw<F, D, N, K, ELL>],
+        ksk: &LweKeySwitchKey<F, D, n, N, K, ELL>,
+    ) -> Self {
+        PbsInputs {
+            cts: vec![ct],
+            testv,
+            bsk,
+            ksk: KeySwitchGgsws::Lwe(ksk.step_ggsws()),
+            num_pbs: 1,
+        }
+    }
+
+    // the PBSs of a batch of ciphertexts with the same keys and test vector
+    pub fn new_batch(
+        cts: Vec<&'a [F]>,
+        testv: &'a Poly<F, D, N>,
+        bsk: &'a [Ggsw<F, D, N, K, ELL>],
+        ksk: &'a Ggsw<F, D, N, K, ELL>,
+    ) -> Self {
+        PbsInputs {
+            cts,
+            testv,
+            bsk,
+            ksk: KeySwitchGgsws::Glwe(ksk),
+            num_pbs: 1,
+        }
+    }
+
+    // a chain of `num_pbs` PBSs starting from the PBS of these inputs
+    pub fn chain(self, num_pbs: usize) -> Self {
+        PbsInputs { num_pbs, ..self }
+    }
+
+    pub fn key_switch(&self) -> KeySwitchMode {
+        match self.ksk {
+            KeySwitchGgsws::Glwe(_) => KeySwitchMode::Glwe,
+            KeySwitchGgsws::Lwe(_) => KeySwitchMode::Lwe,
+        }
+    }
+
+    // the GGSWs consumed by the steps of the key switch, one per step
+    fn key_switch_ggsws(&self) -> &[Ggsw<F, D, N, K, ELL>] {
+        match &self.ksk {
+            KeySwitchGgsws::Glwe(ksk) => std::slice::from_ref(*ksk),
+            KeySwitchGgsws::Lwe(ksk_ggsws) => ksk_ggsws,
+        }
+    }
+}
+
+// The statement proven by `verified_pbs`: the PBS of the LWE ciphertext with hash `ct_hash`,
+// using the test vector `testv` and the keys with hashes `bsk_hash` and `ksk_hash`. The
+// cyclic circuit exposes its `digest` as a public input, so a verifier who only holds a
+// published digest can check a proof without the ciphertext or the keys.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PbsStatement<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+> {
+    pub ct_hash: HashOut<F>,
+    pub testv: Poly<F, D, N>,
+    pub bsk_hash: HashOut<F>,
+    pub ksk_hash: HashOut<F>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>
+    PbsStatement<F, D, N, K>
+{
+    // The LWE hash chain absorbs the elements of all the ciphertexts of a batch in each step,
+    // but only the input ciphertext of the first PBS of a chain. The BSK hash chain runs over
+    // all the PBSs of a chain, and so absorbs the keys `num_pbs` times (except for the last
+    // KSK). The KSK of the LWE key switch is hashed chunk by chunk as it is absorbed by the
+    // steps of the key switch.
+    pub fn new<const n: usize, const ELL: usize>(inputs: &PbsInputs<F, D, N, K, ELL>) -> Self {
+        assert!(!inputs.cts.is_empty(), "a batch has at least one ciphertext");
+        assert!(inputs.num_pbs > 0, "a chain has at least one PBS");
+        let ksk_ggsws = inputs.key_switch_ggsws();
+        let bsk_data = hash_bsk_data(inputs.bsk);
+        let (bsk_hash, ksk_hash) = match inputs.ksk {
+            KeySwitchGgsws::Glwe(ksk) => {
+                let ksk_hash = ggsw_digest(ksk);
+                let pbs_data: Vec<Vec<F>> = bsk_data
+                    .into_iter()
+                    .chain(once(ksk_hash.elements.to_vec()))
+                    .collect();
+                let chain_data: Vec<Vec<F>> = pbs_data
+                    .iter()
+                    .cycle()
+                    .take(inputs.num_pbs * pbs_data.len() - 1)
+                    .cloned()
+                    .collect();
+                (hash_output(&chain_data), ksk_hash)
+            }
+            KeySwitchGgsws::Lwe(_) => (
+                hash_output(&bsk_data),
+                hash_output(&hash_ksk_data(ksk_ggsws)),
+            ),
+        };
+        PbsStatement {
+            ct_hash: hash_output(&hash_lwe_data::<F, n>(&inputs.cts, ksk_ggsws.len())),
+            testv: inputs.testv.clone(),
+            bsk_hash,
+            ksk_hash,
+        }
+    }
+
+    // the commitment to the statement, computed in the same way as in the last step of the
+    // cyclic circuit
+    pub fn digest(&self) -> HashOut<F> {
+        let acc_init = Glwe::<F, D, N, K>::trivial_ct(self.testv.clone());
+        PoseidonHash::hash_no_pad(
+            &self
+                .ct_hash
+                .elements
+                .into_iter()
+                .chain(acc_init.flatten())
+                .chain(self.bsk_hash.elements)
+                .chain(self.ksk_hash.elements)
+                .collect::<Vec<F>>(),
+        )
+    }
+}
+
+// Computes the public inputs of the final proof of `verified_pbs` (without the verifier
+// data) natively, i.e. without building the circuit or proving anything.
+pub fn pbs_public_inputs<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+    ksk: &Ggsw<F, D, N, K, ELL>,
+) -> Vec<F> {
+    let acc_init = Glwe::<F, D, N, K>::trivial_ct(testv.clone());
+    let out_ct = pbs::<F, D, n, N, K, ELL, LOGB>(ct, testv, bsk, ksk);
+    let statement =
+        PbsStatement::<F, D, N, K>::new::<n, ELL>(&PbsInputs::new(ct, testv, bsk, ksk));
+    // the key switching key is absorbed into the BSK hash chain in the last step
+    let hash_bsk_out = PoseidonHash::hash_no_pad(
+        &statement
+            .bsk_hash
+            .elements
+            .into_iter()
+            .chain(statement.ksk_hash.elements)
+            .collect::<Vec<F>>(),
+    );
+    acc_init
+        .flatten()
+        .into_iter()
+        .chain(once(F::from_canonical_usize(n + 2)))
+        .chain(out_ct.flatten())
+        .chain(hash_bsk_out.elements)
+        .chain(statement.ct_hash.elements)
+        .chain(statement.digest().elements)
+        .collect()
+}
+
+// Computes the public inputs of the final proof of `verified_pbs` with the LWE key switch
+// (without the verifier data) natively
+pub fn pbs_lwe_public_inputs<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+    ksk: &LweKeySwitchKey<F, D, n, N, K, ELL>,
+) -> Vec<F> {
+    let acc_init = Glwe::<F, D, N, K>::trivial_ct(testv.clone());
+    let acc = blind_rotate::<F, D, n, N, K, ELL, LOGB>(ct, testv, bsk);
+    let out_ct = key_switch::<F, D, n, N, K, ELL, LOGB>(&acc, ksk);
+    let statement =
+        PbsStatement::<F, D, N, K>::new::<n, ELL>(&PbsInputs::new_lwe(ct, testv, bsk, ksk));
+    let num_steps = n + 1 + LweKeySwitchKey::<F, D, n, N, K, ELL>::NUM_CHUNKS;
+    // the key switch shifts the whole mask out of the accumulator, and the BSK hash chain ends
+    // with the KSK
+    acc_init
+        .flatten()
+        .into_iter()
+        .chain(once(F::from_canonical_usize(num_steps)))
+        .chain(repeat_n(F::ZERO, (K - 1) * N))
+        .chain(acc.polys[K - 1].coeffs)
+        .chain(statement.ksk_hash.elements)
+        .chain(statement.ct_hash.elements)
+        .chain(statement.digest().elements)
+        .chain(out_ct)
+        .chain(once(F::ONE))
+        .chain(statement.bsk_hash.elements)
+        .collect()
+}
+
+pub(super) fn hash_select<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    control: BoolTarget,
+    left: HashOutTarget,
+    right: HashOutTarget,
+) -> HashOutTarget {
+    HashOutTarget {
+        elements: from_fn(|i| builder.select(control, left.elements[i], right.elements[i])),
+    }
+}
+
+// the targets of the step circuit that are connected to the previous proof (or to the initial
+// values in the base case) in `build_cyclic_circuit`
+struct StepCircuitTargets<const N: usize, const K: usize, const ELL: usize> {
+    // the mask element and the GGSW consumed by each of the steps proven at once
+    mask_elements: Vec<Target>,
+    acc_init: GlweCt<N, K>,
+    ggsws: Vec<GgswCt<N, K, ELL>>,
+    acc_in: GlweCt<N, K>,
+    // the number of steps proven so far
+    counter_in: Target,
+    bsk_hash_in: HashOutTarget,
+    lwe_hash_in: HashOutTarget,
+    lwe_key_switch: Option<LweKeySwitchTargets>,
+    chain: Option<ChainTargets>,
+    batch: BatchTargets<N, K>,
+}
+
+// Rotates the accumulator by the mask element, which in the first step is actually the body and
+// needs to be negated; returns the rotated accumulator and its difference to the accumulator,
+// the input of the CMUX
+fn rotate_by_mask<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    acc_in: &GlweCt<N, K>,
+    mask_element: Target,
+    first_step: BoolTarget,
+) -> (GlweCt<N, K>, GlweCt<N, K>) {
+    let neg_mask = builder.neg(mask_element);
+    let first_negated_mask = builder.select(first_step, neg_mask, mask_element);
+
+    let shifted_glwe = rotate_glwe(builder, acc_in, first_negated_mask);
+    let diff_glwe = shifted_glwe.sub(builder, acc_in);
+    (shifted_glwe, diff_glwe)
+}
+
+// a step of the PBS with the GLWE key switch: the rotation by the body in the first step, the
+// CMUX with a GGSW of the BSK, and the external product with the GGSW of the KSK in the last step
+pub(super) fn glwe_pbs_step<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const LOGB: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    ggsw: &GgswCt<N, K, ELL>,
+    acc_in: &GlweCt<N, K>,
+    mask_element: Target,
+    first_step: BoolTarget,
+    last_step: BoolTarget,
+) -> GlweCt<N, K> {
+    let (shifted_glwe, diff_glwe) = rotate_by_mask(builder, acc_in, mask_element, first_step);
+    let xprod_in = glwe_select(builder, last_step, acc_in, &diff_glwe);
+    let xprod_out = ggsw.external_product::<F, D, LOGB>(builder, &xprod_in);
+    let cmux_out = xprod_out.add(builder, acc_in);
+
+    // in the last step we don't do a cmux, but just an external product for key switch
+    let cmux_or_exprod = glwe_select(builder, last_step, &xprod_out, &cmux_out);
+
+    // in the first step (body) we don't apply the full CMUX, just the rotation
+    glwe_select(builder, first_step, &shifted_glwe, &cmux_or_exprod)
+}
+
+fn build_step_circuit<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const LOGB: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const n: usize,
+    const STEPS_PER_PROOF: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    options: PbsCircuitOptions,
+) -> StepCircuitTargets<N, K, ELL> {
+    let acc_init = GlweCt::<N, K>::new_from_builder(builder);
+    let ggsws: Vec<GgswCt<N, K, ELL>> = (0..STEPS_PER_PROOF)
+        .map(|_| GgswCt::new_from_builder(builder))
+        .collect();
+    let ggsw = &ggsws[0];
+    acc_init.register(builder);
+    let prev_acc = GlweCt::<N, K>::new_from_builder(builder);
+    let counter = builder.add_virtual_public_input();
+    let counter_in = builder.add_virtual_target();
+    let one = builder.one();
+    // the first step of the key switch, after the rotation by the body and the n CMUXs
+    let key_switch_target = builder.constant(F::from_canonical_usize(n + 2));
+
+    // the position of the (first) step in its PBS, which follows the counter unless the circuit
+    // proves a chain of PBSs, where it starts over after the key switch of each PBS
+    let chain_step_in = options.chain.then(|| builder.add_virtual_target());
+    let step = match chain_step_in {
+        Some(step_in) => chain_step(builder, step_in, key_switch_target),
+        None => builder.add(counter_in, one),
+    };
+    let first_step = builder.is_equal(step, one);
+    let first_key_switch = builder.is_equal(step, key_switch_target);
+
+    let mask_elements = builder.add_virtual_targets(STEPS_PER_PROOF);
+    let mask_element = mask_elements[0];
+
+    let chain = chain_step_in.map(|step_in| {
+        chain_pbs::<F, D, n, N, K>(builder, step_in, step, first_step, &prev_acc, mask_element)
+    });
+    // each PBS of a chain starts from the test vector
+    let current_acc_in = match chain {
+        Some(_) => glwe_select(builder, first_step, &acc_init, &prev_acc),
+        None => GlweCt::new_from_targets(&prev_acc.flatten()),
+    };
+    let batch = BatchTargets::new(builder, options.batch_size);
+
+    let current_bsk_hash_in = builder.add_virtual_hash();
+    let ggsw_digests: Vec<HashOutTarget> = ggsws
+        .iter()
+        .map(|ggsw| builder.hash_n_to_hash_no_pad::<PoseidonHash>(ggsw.flatten()))
+        .collect();
+    let ggsw_digest = ggsw_digests[0];
+
+    // whether each step after the first of the proof is still part of the PBS
+    let mut steps_active = Vec::new();
+    let (current_acc_out, current_bsk_hash_out, bsk_hash, ksk_hash, lwe_key_switch) =
+        match options.key_switch {
+            KeySwitchMode::Glwe => {
+                let mut acc_out = glwe_pbs_step::<F, D, LOGB, N, K, ELL>(
+                    builder,
+                    ggsw,
+                    &current_acc_in,
+                    mask_element,
+                    first_step,
+                    first_key_switch,
+                );
+
+                // the BSK hash chain absorbs the digest of each GGSW, so that the hash of the BSK
+                // (the chain before the last step) and of the KSK (the last digest) can be told apart
+                let mut bsk_hash_out = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+                    current_bsk_hash_in
+                        .elements
+                        .into_iter()
+                        .chain(ggsw_digest.elements)
+                        .collect(),
+                );
+                let (mut bsk_hash, mut ksk_hash) = (current_bsk_hash_in, ggsw_digest);
+
+                // The other steps of the proof are CMUXs or the key switch, and leave the
+                // accumulator and the hash chain unchanged once the key switch is done, so that
+                // the last proof of the PBS can run past its end.
+                let not_first_step = builder._false();
+                let mut sub_step = step;
+                let mut active = builder.not(first_key_switch);
+                for (sub_ggsw, (&sub_mask_element, &sub_ggsw_digest)) in ggsws
+                    .iter()
+                    .zip(mask_elements.iter().zip(&ggsw_digests))
+                    .skip(1)
+                {
+                    sub_step = builder.add(sub_step, one);
+                    let last_step = builder.is_equal(sub_step, key_switch_target);
+                    let sub_acc_out = glwe_pbs_step::<F, D, LOGB, N, K, ELL>(
+                        builder,
+                        sub_ggsw,
+                        &acc_out,
+                        sub_mask_element,
+                        not_first_step,
+                        last_step,
+                    );
+                    acc_out = glwe_select(builder, active, &sub_acc_out, &acc_out);
+                    let sub_bsk_hash_out = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+                        bsk_hash_out
+                            .elements
+                            .into_iter()
+                            .chain(sub_ggsw_digest.elements)
+                            .collect(),
+                    );
+                    bsk_hash = hash_select(builder, last_step, bsk_hash_out, bsk_hash);
+                    ksk_hash = hash_select(builder, last_step, sub_ggsw_digest, ksk_hash);
+                    bsk_hash_out = hash_select(builder, active, sub_bsk_hash_out, bsk_hash_out);
+                    steps_active.push(active);
+                    let not_last_step = builder.not(last_step);
+                    active = builder.and(active, not_last_step);
+                }
+                (acc_out, bsk_hash_out, bsk_hash, ksk_hash, None)
+            }
+            KeySwitchMode::Lwe => {
+                let (shifted_glwe, diff_glwe) =
+                    rotate_by_mask(builder, &current_acc_in, mask_element, first_step);
+                let xprod_out = ggsw.external_product::<F, D, LOGB>(builder, &diff_glwe);
+                let cmux_out = xprod_out.add(builder, &current_acc_in);
+                let blind_rotation_out = glwe_select(builder, first_step, &shifted_glwe, &cmux_out);
+
+                let (acc_out, bsk_hash_out, bsk_hash, state) =
+                    lwe_key_switch_step::<F, D, LOGB, n, N, K, ELL>(
+                        builder,
+                        ggsw,
+                        ggsw_digest,
+                        &current_acc_in,
+                        &blind_rotation_out,
+                        first_key_switch,
+                        current_bsk_hash_in,
+                    );
+                // the KSK is hashed by the BSK hash chain restarted for it
+                (acc_out, bsk_hash_out, bsk_hash, bsk_hash_out, Some(state))
+            }
+        };
+    current_acc_out.register(builder);
+    // the counter only counts the steps of the proof that are part of the PBS
+    let mut counter_out = builder.add(counter_in, one);
+    for active in &steps_active {
+        counter_out = builder.add(counter_out, active.target);
+    }
+    builder.connect(counter, counter_out);
+    let batch_accs_out =
+        batch_pbs_steps::<F, D, LOGB, N, K, ELL>(builder, &batch, ggsw, first_step, first_key_switch);
+
+    let current_lwe_hash_in = builder.add_virtual_hash();
+    let mut lwe_hash_absorbed = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+        current_lwe_hash_in
+            .elements
+            .into_iter()
+            .chain(once(mask_element))
+            .chain(batch.lwe_cts.iter().copied())
+            .collect(),
+    );
+    for (&sub_mask_element, &active) in mask_elements[1..].iter().zip(&steps_active) {
+        let sub_lwe_hash_absorbed = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+            lwe_hash_absorbed
+                .elements
+                .into_iter()
+                .chain(once(sub_mask_element))
+                .collect(),
+        );
+        lwe_hash_absorbed = hash_select(builder, active, sub_lwe_hash_absorbed, lwe_hash_absorbed);
+    }
+    // the input ciphertexts of the PBSs after the first in a chain are not part of the statement
+    let current_lwe_hash_out = match &chain {
+        Some(chain) => hash_select(builder, chain.first_pbs, lwe_hash_absorbed, current_lwe_hash_in),
+        None => lwe_hash_absorbed,
+    };
+
+    // commitment to the whole statement, see `PbsStatement`; it is only meaningful in the
+    // last step, where the key switch is complete and so is the LWE hash chain
+    let statement_digest = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+        current_lwe_hash_out
+            .elements
+            .into_iter()
+            .chain(acc_init.flatten())
+            .chain(bsk_hash.elements)
+            .chain(ksk_hash.elements)
+            .collect(),
+    );
+
+    builder.register_public_inputs(&current_bsk_hash_out.elements);
+    builder.register_public_inputs(&current_lwe_hash_out.elements);
+    builder.register_public_inputs(&statement_digest.elements);
+
+    let lwe_key_switch = lwe_key_switch.map(|state| state.register(builder));
+    let chain = chain.map(|state| state.register(builder));
+    for batch_acc_out in &batch_accs_out {
+        batch_acc_out.register(builder);
+    }
+    if options.extract_lwe {
+        register_sample_extraction::<F, D, n, N, K>(builder, &current_acc_out);
+    }
+
+    StepCircuitTargets {
+        mask_elements,
+        acc_init,
+        ggsws,
+        acc_in: prev_acc,
+        counter_in,
+        bsk_hash_in: current_bsk_hash_in,
+        lwe_hash_in: current_lwe_hash_in,
+        lwe_key_switch,
+        chain,
+        batch,
+    }
+}
+
+// A proof of the first `step` steps of `verified_pbs`: step 0 rotates the accumulator by the
+// body, step i + 1 is the CMUX with the i-th GGSW of the BSK, and step n + 1 is the key switch
+// (which takes `LweKeySwitchKey::NUM_CHUNKS` steps in `KeySwitchMode::Lwe`).
+// `PbsProver::resume` continues proving from it.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Checkpoint<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    // the id of the parameter set of the PBS, see `CheckpointConfig::params`
+    pub params: String,
+    pub step: usize,
+    pub proof: ProofWithPublicInputs<F, C, D>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    Checkpoint<F, C, D>
+{
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read checkpoint {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse checkpoint {}", path.display()))
+    }
+
+    // the checkpoint is written to a temporary file first, so that a crash while writing it
+    // does not destroy the previous one
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_string(self)?)
+            .with_context(|| format!("failed to write checkpoint {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("failed to write checkpoint {}", path.display()))
+    }
+}
+
+// Where and how often the latest proof is persisted while proving a PBS
+pub struct CheckpointConfig {
+    pub path: PathBuf,
+    // a checkpoint is written after every `interval` steps
+    pub interval: NonZeroUsize,
+    // the id of the parameter set of the keys (see `ParameterSet`), recorded in the checkpoint
+    // so that a checkpoint written for another set is not resumed
+    pub params: &'static str,
+}
+
+// One of the independent PBSs proven by `PbsProver::prove_many`, which share the keys
+pub struct PbsJob<F: RichField + Extendable<D>, const D: usize, const N: usize> {
+    pub ct: Vec<F>,
+    pub testv: Poly<F, D, N>,
+}
+
+// Runs `f` on each item of `items` and its index on `num_workers` threads. Each worker takes
+// the next item once it is done with the previous one. The workers advance `items` under a
+// lock, so producing an item should be cheap (e.g. a path or an index), and any expensive work
+// on it be left to `f`. Stops at the first error, once the items in progress are done.
+pub(super) fn try_for_each_parallel<T, I>(
+    items: I,
+    num_workers: NonZeroUsize,
+    f: impl Fn(usize, T) -> Result<()> + Sync,
+) -> Result<()>
+where
+    I: Iterator<Item = T> + Send,
+{
+    let items = Mutex::new(items.enumerate());
+    let failed = AtomicBool::new(false);
+    let worker = || -> Result<()> {
+        while !failed.load(Ordering::Relaxed) {
+            // the lock is only held while the next item is taken
+            let Some((index, item)) = items.lock().unwrap().next() else {
+                break;
+            };
+            if let Err(err) = f(index, item) {
+                failed.store(true, Ordering::Relaxed);
+                return Err(err);
+            }
+        }
+        Ok(())
+    };
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..num_workers.get()).map(|_| scope.spawn(worker)).collect();
+        workers
+            .into_iter()
+            .try_for_each(|worker| worker.join().expect("a worker panicked"))
+    })
+}
+
+// whether the steps of a PBS are checkpointed, and the checkpoint they are resumed from
+enum Checkpointing<'a, F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    Disabled,
+    Save(&'a CheckpointConfig),
+    Resume(&'a CheckpointConfig, Box<Checkpoint<F, C, D>>),
+}
+
+// The outputs of the PBSs of `PbsInputs`, which are published by their proof
+#[derive(Debug, PartialEq)]
+pub struct PbsOutputs<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize> {
+    // the output GLWE ciphertext of each PBS of a batch, or of the last PBS of a chain; none
+    // with the LWE key switch
+    pub glwes: Vec<Glwe<F, D, N, K>>,
+    // the output LWE ciphertext of the LWE key switch, or the sample extraction of the output
+    // GLWE ciphertext with `PbsCircuitOptions::extract_lwe` (under the partial output key)
+    pub lwe: Option<Vec<F>>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize>
+    PbsOutputs<F, D, N, K>
+{
+    // the outputs in the public inputs of a proof of the circuit built with `options`
+    fn from_proof<C: GenericConfig<D, F = F>, const n: usize>(
+        options: PbsCircuitOptions,
+        proof: &ProofWithPublicInputs<F, C, D>,
+    ) -> Self {
+        let ranges = PublicInputRanges::new::<n, N, K>(options);
+        let pis = |range: (usize, usize)| proof.public_inputs[range.0..range.1].to_vec();
+        match &ranges.lwe_key_switch {
+            Some(lwe_ranges) => PbsOutputs {
+                glwes: Vec::new(),
+                lwe: Some(pis(lwe_ranges.lwe_acc)),
+            },
+            None => PbsOutputs {
+                glwes: batch_outputs::<F, C, D, n, N, K>(options, proof),
+                lwe: ranges.extracted_lwe.map(pis),
+            },
+        }
+    }
+
+    // the output GLWE ciphertext of a single PBS (or of the last PBS of a chain)
+    pub fn into_glwe(self) -> Result<Glwe<F, D, N, K>> {
+        match <[_; 1]>::try_from(self.glwes) {
+            Ok([glwe]) => Ok(glwe),
+            Err(glwes) => Err(anyhow!(
+                "expected the output GLWE ciphertext of a single PBS, found {}",
+                glwes.len()
+            )),
+        }
+    }
+}
+
+// the outputs of the PBSs and their proof
+pub type PbsProof<F, C, const D: usize, const N: usize, const K: usize> =
+    (PbsOutputs<F, D, N, K>, ProofWithPublicInputs<F, C, D>);
+
+// the outputs of the PBSs, their proof and the cyclic circuit
+pub type PbsOutput<F, C, const D: usize, const N: usize, const K: usize> = (
+    PbsOutputs<F, D, N, K>,
+    ProofWithPublicInputs<F, C, D>,
+    CircuitData<F, C, D>,
+);
+
+// the secret keys used to log the error of the accumulator after each step (for debugging)
+struct DebugKeys<'a, F: RichField + Extendable<D>, const D: usize, const N: usize> {
+    glwe_key: Option<&'a [Poly<F, D, N>]>,
+    lwe_key: Option<&'a [F]>,
+    ksk_key: Option<&'a [Poly<F, D, N>]>,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize, const N: usize> DebugKeys<'_, F, D, N> {
+    fn none() -> Self {
+        DebugKeys {
+            glwe_key: None,
+            lwe_key: None,
+            ksk_key: None,
+        }
+    }
+}
+
+// how the steps are proven: the keys to log the errors with, the checkpoints, and the job the
+// progress is reported for if the PBS is one of `PbsProver::prove_many`
+struct StepRun<
+    'a,
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+    const N: usize,
+> {
+    debug_keys: DebugKeys<'a, F, D, N>,
+    checkpointing: Checkpointing<'a, F, C, D>,
+    job: Option<usize>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize, const N: usize>
+    Default for StepRun<'_, F, C, D, N>
+{
+    fn default() -> Self {
+        StepRun {
+            debug_keys: DebugKeys::none(),
+            checkpointing: Checkpointing::Disabled,
+            job: None,
+        }
+    }
+}
+
+// Proves the PBSs of `inputs` with the cyclic circuit built with `options`; returns their
+// outputs, the proof and the circuit. The secret keys are only used to log the error of the
+// accumulator after each step.
+pub fn verified_pbs<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F> + 'static,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    options: PbsCircuitOptions,
+    inputs: &PbsInputs<F, D, N, K, ELL>,
+    debug_glwe_key_maybe: Option<&GlweSecretKey<F, D, N, K>>,
+    debug_lwe_key_maybe: Option<&LweSecretKey<F, D, n>>,
+    debug_ksk_key_maybe: Option<&GlweSecretKey<F, D, N, K>>,
+) -> Result<PbsOutput<F, C, D, N, K>>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+{
+    let debug_keys = DebugKeys {
+        glwe_key: debug_glwe_key_maybe.map(GlweSecretKey::polys),
+        lwe_key: debug_lwe_key_maybe.map(LweSecretKey::elements),
+        ksk_key: debug_ksk_key_maybe.map(GlweSecretKey::polys),
+    };
+    let prover = PbsProver::<F, C, D, n, N, K, ELL, LOGB>::with_options(options);
+    let proof = prover.prove_steps(
+        inputs,
+        StepRun {
+            debug_keys,
+            ..Default::default()
+        },
+    )?;
+    let outputs = PbsOutputs::from_proof::<C, n>(options, &proof);
+    Ok((outputs, proof, prover.circuit_data))
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        const D: usize,
+        const n: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+        const LOGB: usize,
+        const STEPS_PER_PROOF: usize,
+    > Default for PbsProver<F, C, D, n, N, K, ELL, LOGB, STEPS_PER_PROOF>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+    C: 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the positions of the public inputs of the cyclic circuit (before the verifier data)
+struct PublicInputRanges {
+    acc_init: (usize, usize),
+    counter: usize,
+    latest_acc: (usize, usize),
+    hash_bsk_out: (usize, usize),
+    hash_lwe_out: (usize, usize),
+    statement_digest: (usize, usize),
+    lwe_key_switch: Option<LweKeySwitchRanges>,
+    chain: Option<ChainRanges>,
+    // the latest accumulators of the other ciphertexts of a batch
+    batch_accs: Option<(usize, usize)>,
+    // the sample extraction of the output, if the circuit publishes it
+    extracted_lwe: Option<(usize, usize)>,
+}
+
+impl PublicInputRanges {
+    fn new<const n: usize, const N: usize, const K: usize>(options: PbsCircuitOptions) -> Self {
+        let acc_init = (0, GlweCt::<N, K>::num_targets());
+        let counter = acc_init.1;
+        let latest_acc = (counter + 1, counter + 1 + GlweCt::<N, K>::num_targets());
+        let hash_bsk_out = (latest_acc.1, latest_acc.1 + NUM_HASH_OUT_ELTS);
+        let hash_lwe_out = (hash_bsk_out.1, hash_bsk_out.1 + NUM_HASH_OUT_ELTS);
+        let statement_digest = (hash_lwe_out.1, hash_lwe_out.1 + NUM_HASH_OUT_ELTS);
+        let lwe_key_switch = (options.key_switch == KeySwitchMode::Lwe)
+            .then(|| LweKeySwitchRanges::new::<n>(statement_digest.1));
+        let lwe_key_switch_end = lwe_key_switch
+            .as_ref()
+            .map_or(statement_digest.1, |ranges| ranges.hash_bsk.1);
+        let chain = options
+            .chain
+            .then(|| ChainRanges::new::<n>(lwe_key_switch_end));
+        let chain_end = chain.as_ref().map_or(lwe_key_switch_end, |ranges| ranges.pending_ct.1);
+        let batch_accs = (options.batch_size > 1).then_some((
+            chain_end,
+            chain_end + (options.batch_size - 1) * GlweCt::<N, K>::num_targets(),
+        ));
+        let batch_end = batch_accs.map_or(chain_end, |range| range.1);
+        let extracted_lwe = options.extract_lwe.then_some((batch_end, batch_end + n + 1));
+        PublicInputRanges {
+            acc_init,
+            counter,
+            latest_acc,
+            hash_bsk_out,
+            hash_lwe_out,
+            statement_digest,
+            lwe_key_switch,
+            chain,
+            batch_accs,
+            extracted_lwe,
+        }
+    }
+}
+
+// the targets of the cyclic circuit that are assigned in each step
+struct StepTargets<const D: usize, const N: usize, const K: usize, const ELL: usize> {
+    // the LWE element and the GGSW of each of the steps proven at once
+    lwe_cts: Vec<Target>,
+    // the LWE elements of the other ciphertexts of a batch
+    batch_lwe_cts: Vec<Target>,
+    ggsws: Vec<GgswCt<N, K, ELL>>,
+    condition: BoolTarget,
+    inner_cyclic_proof_with_pis: ProofWithPublicInputsTarget<D>,
+    verifier_data: VerifierCircuitTarget,
+}
+
+// The cyclic circuit of `verified_pbs`, which only depends on the parameters (n, N, K, ELL,
+// LOGB) and the `PbsCircuitOptions`. Building it takes a good part of the time of a PBS proof,
+// so a `PbsProver` is built once (or loaded from disk) and then proves any number of PBSs, e.g.
+// of many ciphertexts under the same BSK.
+//
+// Each proof proves `STEPS_PER_PROOF` steps of the PBS (only with the GLWE key switch, without
+// chains or batches), which trades a larger circuit for fewer recursive verifications; the last
+// proof of a PBS skips the steps past its end.
+pub struct PbsProver<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+    const STEPS_PER_PROOF: usize = 1,
+> {
+    pub circuit_data: CircuitData<F, C, D>,
+    targets: StepTargets<D, N, K, ELL>,
+    options: PbsCircuitOptions,
+    // notified after each proof, a progress bar unless set with `set_progress`
+    progress: Box<dyn ProgressObserver>,
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        const D: usize,
+        const n: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+        const LOGB: usize,
+        const STEPS_PER_PROOF: usize,
+    > PbsProver<F, C, D, n, N, K, ELL, LOGB, STEPS_PER_PROOF>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+    C: 'static,
+{
+    pub fn new() -> Self {
+        Self::with_options(PbsCircuitOptions::default())
+    }
+
+    pub fn with_mode(key_switch: KeySwitchMode) -> Self {
+        Self::with_options(PbsCircuitOptions {
+            key_switch,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_options(options: PbsCircuitOptions) -> Self {
+        assert!(
+            !(options.extract_lwe && options.key_switch == KeySwitchMode::Lwe),
+            "the output of the LWE key switch is already an LWE ciphertext"
+        );
+        assert!(
+            !(options.chain && options.key_switch == KeySwitchMode::Lwe),
+            "only PBSs with the GLWE key switch can be chained"
+        );
+        assert!(options.batch_size > 0, "a batch has at least one ciphertext");
+        assert!(
+            options.batch_size == 1
+                || (options.key_switch == KeySwitchMode::Glwe && !options.chain && !options.extract_lwe),
+            "only PBSs with the GLWE key switch can be batched, without chains or sample extraction"
+        );
+        assert!(STEPS_PER_PROOF > 0, "a proof proves at least one step");
+        assert!(
+            STEPS_PER_PROOF == 1
+                || (options.key_switch == KeySwitchMode::Glwe && !options.chain && options.batch_size == 1),
+            "only PBSs with the GLWE key switch, without chains or batches, can prove several steps per proof"
+        );
+        info!(
+            "Parameters: n={n}, N={N}, k={}, logB={LOGB}, ell={ELL}, steps per proof={STEPS_PER_PROOF}, {options:?}",
+            K - 1
+        );
+
+        let (circuit_data, targets) = build_cyclic_circuit_data::<F, C, D, _>(|common_data| {
+            Self::build_cyclic_circuit(common_data, options)
+        });
+        PbsProver {
+            circuit_data,
+            targets,
+            options,
+            progress: Box::new(ProgressBar::default()),
+        }
+    }
+
+    // replaces the progress bar shown while proving, e.g. with `JsonLinesProgress` or `NoProgress`
+    pub fn set_progress(&mut self, progress: impl ProgressObserver + 'static) {
+        self.progress = Box::new(progress);
+    }
+
+    // builds the cyclic circuit, which verifies proofs with the given common data (up to the
+    // number of public inputs, which is set here)
+    fn build_cyclic_circuit(
+        mut common_data: CommonCircuitData<F, D>,
+        options: PbsCircuitOptions,
+    ) -> (CircuitBuilder<F, D>, StepTargets<D, N, K, ELL>) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config.clone());
+
+        let StepCircuitTargets {
+            mask_elements: lwe_cts,
+            acc_init,
+            ggsws,
+            acc_in: current_acc_in,
+            counter_in,
+            bsk_hash_in: current_bsk_hash_in,
+            lwe_hash_in: current_lwe_hash_in,
+            lwe_key_switch,
+            chain,
+            batch,
+        } = build_step_circuit::<F, D, LOGB, N, K, ELL, n, STEPS_PER_PROOF>(&mut builder, options);
+        let ranges = PublicInputRanges::new::<n, N, K>(options);
+
+        let verifier_data_target = builder.add_verifier_data_public_inputs();
+        common_data.num_public_inputs = builder.num_public_inputs();
+
+        // Unpack inner proof's public inputs.
+        let inner_cyclic_proof_with_pis = builder.add_virtual_proof_with_pis(&common_data);
+        let inner_cyclic_pis = &inner_cyclic_proof_with_pis.public_inputs;
+        let inner_cyclic_acc_init = &inner_cyclic_pis[ranges.acc_init.0..ranges.acc_init.1];
+        let inner_cyclic_counter = inner_cyclic_pis[ranges.counter];
+        let inner_cyclic_latest_acc =
+            GlweCt::new_from_targets(&inner_cyclic_pis[ranges.latest_acc.0..ranges.latest_acc.1]);
+        let inner_cyclic_latest_bsk_hash =
+            HashOutTarget::try_from(&inner_cyclic_pis[ranges.hash_bsk_out.0..ranges.hash_bsk_out.1])
+                .unwrap();
+        let inner_cyclic_latest_lwe_hash =
+            HashOutTarget::try_from(&inner_cyclic_pis[ranges.hash_lwe_out.0..ranges.hash_lwe_out.1])
+                .unwrap();
+
+        for (initial_target, inner_cyclic_initial_target) in
+            acc_init.flatten().iter().zip(inner_cyclic_acc_init)
+        {
+            builder.connect(*initial_target, *inner_cyclic_initial_target);
+        }
+
+        // base case or not
+        let condition = builder.add_virtual_bool_target_safe();
+        let actual_acc_in = glwe_select(&mut builder, condition, &inner_cyclic_latest_acc, &acc_init);
+        for (left, right) in current_acc_in
+            .flatten()
+            .iter()
+            .zip(actual_acc_in.flatten().iter())
+        {
+            builder.connect(*left, *right);
+        }
+
+        let zero = builder.zero();
+        let actual_bsk_hash_in = inner_cyclic_latest_bsk_hash
+            .elements
+            .map(|t| builder.select(condition, t, zero));
+        let actual_lwe_hash_in = inner_cyclic_latest_lwe_hash
+            .elements
+            .map(|t| builder.select(condition, t, zero));
+
+        builder.connect_hashes(current_bsk_hash_in, HashOutTarget::from(actual_bsk_hash_in));
+
+        builder.connect_hashes(current_lwe_hash_in, HashOutTarget::from(actual_lwe_hash_in));
+
+        if let (Some(targets), Some(lwe_ranges)) = (&lwe_key_switch, &ranges.lwe_key_switch) {
+            connect_lwe_key_switch(&mut builder, targets, lwe_ranges, inner_cyclic_pis, condition);
+        }
+        if let (Some(targets), Some(chain_ranges)) = (&chain, &ranges.chain) {
+            connect_chain(&mut builder, targets, chain_ranges, inner_cyclic_pis, condition);
+        }
+        // the accumulators of the other ciphertexts of a batch start from the test vector as well
+        if let Some(batch_range) = ranges.batch_accs {
+            let inner_cyclic_batch_accs = &inner_cyclic_pis[batch_range.0..batch_range.1];
+            connect_batch(&mut builder, &batch, inner_cyclic_batch_accs, &acc_init, condition);
+        }
+
+        let actual_counter_in = builder.mul(condition.target, inner_cyclic_counter);
+        builder.connect(counter_in, actual_counter_in);
+
+        builder
+            .conditionally_verify_cyclic_proof_or_dummy::<C>(
+                condition,
+                &inner_cyclic_proof_with_pis,
+                &common_data,
+            )
+            .unwrap();
+
+        let targets = StepTargets {
+            lwe_cts,
+            batch_lwe_cts: batch.lwe_cts,
+            ggsws,
+            condition,
+            inner_cyclic_proof_with_pis,
+            verifier_data: verifier_data_target,
+        };
+        (builder, targets)
+    }
+
+    // Proves the PBSs of `inputs`, see `PbsInputs`; returns their outputs and the proof
+    pub fn prove(&self, inputs: &PbsInputs<F, D, N, K, ELL>) -> Result<PbsProof<F, C, D, N, K>> {
+        let proof = self.prove_steps(inputs, StepRun::default())?;
+        Ok((PbsOutputs::from_proof::<C, n>(self.options, &proof), proof))
+    }
+
+    // the output ciphertext in the public inputs of a proof of the GLWE key switch
+    fn glwe_output(proof: &ProofWithPublicInputs<F, C, D>) -> Glwe<F, D, N, K> {
+        let ranges = PublicInputRanges::new::<n, N, K>(PbsCircuitOptions::default());
+        Glwe::from_slice(&proof.public_inputs[ranges.latest_acc.0..ranges.latest_acc.1])
+    }
+
+    // Same as `prove`, but persists the latest proof as a `Checkpoint` every `config.interval`
+    // steps, so that `resume` can continue after a crash
+    pub fn prove_checkpointed(
+        &self,
+        inputs: &PbsInputs<F, D, N, K, ELL>,
+        config: &CheckpointConfig,
+    ) -> Result<PbsProof<F, C, D, N, K>> {
+        let run = StepRun {
+            checkpointing: Checkpointing::Save(config),
+            ..Default::default()
+        };
+        let proof = self.prove_steps(inputs, run)?;
+        Ok((PbsOutputs::from_proof::<C, n>(self.options, &proof), proof))
+    }
+
+    // Continues `prove_checkpointed` from the checkpoint at `config.path`, which must be a
+    // valid proof of the first steps of the same PBS
+    pub fn resume(
+        &self,
+        inputs: &PbsInputs<F, D, N, K, ELL>,
+        config: &CheckpointConfig,
+    ) -> Result<PbsProof<F, C, D, N, K>> {
+        let checkpoint = Checkpoint::load(&config.path)?;
+        ensure!(
+            checkpoint.params == config.params,
+            "the checkpoint {} was written for the parameter set {}, expected {}",
+            config.path.display(),
+            checkpoint.params,
+            config.params
+        );
+        let run = StepRun {
+            checkpointing: Checkpointing::Resume(config, Box::new(checkpoint)),
+            ..Default::default()
+        };
+        let proof = self.prove_steps(inputs, run)?;
+        Ok((PbsOutputs::from_proof::<C, n>(self.options, &proof), proof))
+    }
+
+    // Proves the PBSs of independent jobs under the same keys on `num_workers` threads sharing
+    // the circuit. Each worker pulls the source of the next job from `jobs` (e.g. the path of
+    // its inputs) once it is done with the previous one, loads the job with `load_job`, and
+    // hands its output ciphertext and proof to `on_proof` with the index of the job, so at most
+    // `num_workers` jobs are held in memory at once. Stops at the first error, of a job or of
+    // `on_proof`.
+    pub fn prove_many<I, L, P>(
+        &self,
+        jobs: I,
+        load_job: L,
+        bsk: &[Ggsw<F, D, N, K, ELL>],
+        ksk: &Ggsw<F, D, N, K, ELL>,
+        num_workers: NonZeroUsize,
+        on_proof: P,
+    ) -> Result<()>
+    where
+        I: Iterator + Send,
+        L: Fn(I::Item) -> Result<PbsJob<F, D, N>> + Sync,
+        P: Fn(usize, Glwe<F, D, N, K>, ProofWithPublicInputs<F, C, D>) -> Result<()> + Sync,
+    {
+        let num_done = AtomicUsize::new(0);
+        try_for_each_parallel(jobs, num_workers, |index, source| {
+            load_job(source).and_then(|job| {
+                info!("job {index}: proving");
+                let start = Instant::now();
+                let proof = self.prove_steps(
+                    &PbsInputs::new(&job.ct, &job.testv, bsk, ksk),
+                    StepRun {
+                        job: Some(index),
+                        ..Default::default()
+                    },
+                )?;
+                let out_ct = Self::glwe_output(&proof);
+                let done = num_done.fetch_add(1, Ordering::Relaxed) + 1;
+                info!("job {index}: proven in {:.1?} ({done} jobs done)", start.elapsed());
+                on_proof(index, out_ct, proof)
+            })
+            .with_context(|| format!("job {index} failed"))
+        })
+    }
+
+    // The parameters and circuit options, the circuit data (prover and verifier parts) and the
+    // targets assigned in each step, serialized with plonky2's default gate and generator
+    // serializers
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let generator_serializer = DefaultGeneratorSerializer::<C, D> {
+            _phantom: PhantomData,
+        };
+        let mut bytes = Vec::new();
+        let write = |bytes: &mut Vec<u8>| -> IoResult<()> {
+            let params: Vec<usize> = [n, N, K, ELL, LOGB, STEPS_PER_PROOF]
+                .into_iter()
+                .chain(self.options.to_params())
+                .collect();
+            bytes.write_usize_vec(&params)?;
+            bytes.write_circuit_data(&self.circuit_data, &DefaultGateSerializer, &generator_serializer)?;
+            bytes.write_target_vec(&self.targets.lwe_cts)?;
+            bytes.write_target_vec(&self.targets.batch_lwe_cts)?;
+            let ggsws: Vec<Target> = self.targets.ggsws.iter().flat_map(GgswCt::flatten).collect();
+            bytes.write_target_vec(&ggsws)?;
+            bytes.write_target_bool(self.targets.condition)?;
+            bytes.write_target_proof_with_public_inputs(&self.targets.inner_cyclic_proof_with_pis)?;
+            bytes.write_target_verifier_circuit(&self.targets.verifier_data)
+        };
+        write(&mut bytes).map_err(|e| anyhow!("failed to serialize the PBS circuit: {e}"))?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let generator_serializer = DefaultGeneratorSerializer::<C, D> {
+            _phantom: PhantomData,
+        };
+        let mut buffer = Buffer::new(bytes);
+        let params = buffer
+            .read_usize_vec()
+            .map_err(|e| anyhow!("failed to deserialize the PBS circuit: {e}"))?;
+        ensure!(
+            params.len() == 10 && params[..6] == [n, N, K, ELL, LOGB, STEPS_PER_PROOF],
+            "the PBS circuit was built for the parameters (n, N, K, ELL, LOGB, STEPS_PER_PROOF) = {:?}",
+            &params[..params.len().min(6)]
+        );
+        let options = PbsCircuitOptions::from_params(&params[6..])?;
+        let mut read = || -> IoResult<Self> {
+            Ok(PbsProver {
+                circuit_data: buffer.read_circuit_data(&DefaultGateSerializer, &generator_serializer)?,
+                targets: StepTargets {
+                    lwe_cts: buffer.read_target_vec()?,
+                    batch_lwe_cts: buffer.read_target_vec()?,
+                    ggsws: buffer
+                        .read_target_vec()?
+                        .chunks(GgswCt::<N, K, ELL>::num_targets())
+                        .map(GgswCt::new_from_targets)
+                        .collect(),
+                    condition: buffer.read_target_bool()?,
+                    inner_cyclic_proof_with_pis: buffer.read_target_proof_with_public_inputs()?,
+                    verifier_data: buffer.read_target_verifier_circuit()?,
+                },
+                options,
+                progress: Box::new(ProgressBar::default()),
+            })
+        };
+        let prover = read().map_err(|e| anyhow!("failed to deserialize the PBS circuit: {e}"))?;
+
+        Ok(prover)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_bytes()?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_bytes(&bytes).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    // Proves the steps of the PBSs of `inputs`, the last steps of which consume the GGSWs of
+    // the key switch: the GGSW of the `KeySwitchKey`, or the chunks of the `LweKeySwitchKey`
+    fn prove_steps(
+        &self,
+        inputs: &PbsInputs<F, D, N, K, ELL>,
+        run: StepRun<F, C, D, N>,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        let PbsInputs {
+            ref cts,
+            testv,
+            bsk,
+            num_pbs,
+            ..
+        } = *inputs;
+        let mode = inputs.key_switch();
+        let key_switch_ggsws = inputs.key_switch_ggsws();
+        let StepRun {
+            debug_keys,
+            checkpointing,
+            job,
+        } = run;
+        ensure!(
+            mode == self.options.key_switch,
+            "the PBS circuit was built for the {:?} key switch",
+            self.options.key_switch
+        );
+        ensure!(num_pbs > 0, "a chain has at least one PBS");
+        ensure!(
+            num_pbs == 1 || (self.options.chain && matches!(checkpointing, Checkpointing::Disabled)),
+            "only a circuit built for chains proves several PBSs, and without checkpoints"
+        );
+        ensure!(
+            cts.len() == self.options.batch_size,
+            "the PBS circuit was built for batches of {} ciphertexts",
+            self.options.batch_size
+        );
+        for ct in cts {
+            ensure!(
+                ct.len() == n + 1,
+                "expected an LWE ciphertext of {} elements, found {}",
+                n + 1,
+                ct.len()
+            );
+        }
+        let (checkpoint_config, resume_from) = match checkpointing {
+            Checkpointing::Disabled => (None, None),
+            Checkpointing::Save(config) => (Some(config), None),
+            Checkpointing::Resume(config, checkpoint) => (Some(config), Some(*checkpoint)),
+        };
+        let ranges = PublicInputRanges::new::<n, N, K>(self.options);
+        let initial_pis = Glwe::<F, D, N, K>::trivial_ct(testv.clone()).flatten();
+        let steps_per_pbs = n + 1 + key_switch_ggsws.len();
+        let num_steps = num_pbs * steps_per_pbs;
+
+        // the GGSW consumed by each step, and the LWE elements (one of each ciphertext) consumed
+        // by each step of the current PBS
+        let dummy_ggsw = Ggsw::<F, D, N, K, ELL>::dummy_ct();
+        let step_ggsws: Vec<&Ggsw<F, D, N, K, ELL>> = (0..num_pbs)
+            .flat_map(|_| once(&dummy_ggsw).chain(bsk).chain(key_switch_ggsws))
+            .collect();
+        let mut masks = hash_lwe_data::<F, n>(cts, key_switch_ggsws.len());
+
+        // the test vector rotated in the same way as the accumulator (of the first ciphertext),
+        // to measure its error
+        let ct_switched = mod_switch_ct(cts[0], N);
+        let track_testv = |step: usize, testv_check: Poly<F, D, N>| match (step, debug_keys.lwe_key) {
+            (0, _) => testv_check.left_shift(ct_switched[n]),
+            (step, Some(debug_lwe_key)) if step <= n => testv_check.right_shift(
+                ct_switched[step - 1] * (debug_lwe_key[step - 1].to_canonical_u64() as usize),
+            ),
+            _ => testv_check,
+        };
+
+        let (start, mut proof) = match resume_from {
+            Some(checkpoint) => {
+                let step = checkpoint.step;
+                // the checkpoint must be a proof of the first steps of this very PBS
+                ensure!((1..=num_steps).contains(&step), "invalid checkpoint step {step}");
+                self.circuit_data
+                    .verify(checkpoint.proof.clone())
+                    .context("invalid checkpoint proof")?;
+                check_cyclic_proof_verifier_data(
+                    &checkpoint.proof,
+                    &self.circuit_data.verifier_only,
+                    &self.circuit_data.common,
+                )
+                .context("the checkpoint is a proof of a different circuit")?;
+                let pis = &checkpoint.proof.public_inputs;
+                ensure!(
+                    pis[ranges.counter] == F::from_canonical_usize(step),
+                    "the checkpoint proof is not at step {step}"
+                );
+                let key_data: Vec<Vec<F>> = step_ggsws[..step]
+                    .iter()
+                    .map(|ggsw| ggsw_digest(ggsw).elements.to_vec())
+                    .collect();
+                let lwe_data = &masks[..step];
+                // once the LWE key switch has started, the BSK hash chain only holds the KSK,
+                // and the hash of the BSK is set aside
+                let (bsk_hash, bsk_chain) = match &ranges.lwe_key_switch {
+                    Some(lwe_ranges) if step > n + 1 => (
+                        Some((lwe_ranges.hash_bsk, hash_output(&key_data[..n + 1]))),
+                        hash_output(&key_data[n + 1..]),
+                    ),
+                    _ => (None, hash_output(&key_data)),
+                };
+                ensure!(
+                    pis[ranges.acc_init.0..ranges.acc_init.1] == initial_pis[..]
+                        && pis[ranges.hash_bsk_out.0..ranges.hash_bsk_out.1] == bsk_chain.elements
+                        && pis[ranges.hash_lwe_out.0..ranges.hash_lwe_out.1]
+                            == hash_output(lwe_data).elements
+                        && bsk_hash.is_none_or(|(range, hash)| pis[range.0..range.1] == hash.elements),
+                    "the checkpoint is a proof of a different PBS"
+                );
+                info!("resuming from the checkpoint at step {step}");
+                (step, Some(checkpoint.proof))
+            }
+            None => (0, None),
+        };
+        let mut testv_check = (0..start).fold(testv.clone(), |testv_check, step| {
+            track_testv(step, testv_check)
+        });
+
+        let run_start = Instant::now();
+        for first_step in (start..num_steps).step_by(STEPS_PER_PROOF) {
+            let steps = first_step..(first_step + STEPS_PER_PROOF).min(num_steps);
+            // the next PBS of a chain bootstraps the sample extraction of the output of the
+            // previous one
+            let pbs_step = first_step % steps_per_pbs;
+            if let (0, Some(prev_proof)) = (pbs_step, &proof) {
+                let prev_out_ct = Self::glwe_output(prev_proof).partial_sample_extract(n);
+                masks = hash_lwe_data::<F, n>(&[&prev_out_ct], key_switch_ggsws.len());
+            }
+
+            let mut pw = PartialWitness::new();
+            pw.set_bool_target(self.targets.condition, first_step > 0);
+            // the steps past the end of the PBS are skipped, so any GGSW and mask will do
+            for (i, (ggsw_target, &lwe_ct_target)) in
+                self.targets.ggsws.iter().zip(&self.targets.lwe_cts).enumerate()
+            {
+                let step = first_step + i;
+                let (ggsw, mask) = match step_ggsws.get(step) {
+                    Some(ggsw) => (*ggsw, masks[step % steps_per_pbs][0]),
+                    None => (&dummy_ggsw, F::ZERO),
+                };
+                ggsw_target.assign(&mut pw, ggsw);
+                pw.set_target(lwe_ct_target, mask);
+            }
+            for (&target, &x) in self.targets.batch_lwe_cts.iter().zip(&masks[pbs_step][1..]) {
+                pw.set_target(target, x);
+            }
+            match &proof {
+                Some(inner_proof) => {
+                    pw.set_proof_with_pis_target(&self.targets.inner_cyclic_proof_with_pis, inner_proof)
+                }
+                None => pw.set_proof_with_pis_target::<C, D>(
+                    &self.targets.inner_cyclic_proof_with_pis,
+                    &cyclic_base_proof(
+                        &self.circuit_data.common,
+                        &self.circuit_data.verifier_only,
+                        initial_pis.iter().copied().enumerate().collect(),
+                    ),
+                ),
+            }
+            pw.set_verifier_data_target(&self.targets.verifier_data, &self.circuit_data.verifier_only);
+            let step_start = Instant::now();
+            let mut step_proof = prove::<F, C, D>(
+                &self.circuit_data.prover_only,
+                &self.circuit_data.common,
+                pw,
+                &mut TimingTree::default(),
+            )?;
+            let step_duration = step_start.elapsed();
+            let steps_left = num_steps - steps.end;
+            let eta = run_start
+                .elapsed()
+                .mul_f64(steps_left as f64 / (steps.end - start) as f64);
+            self.progress.on_step(&StepProgress {
+                job,
+                step: steps.end,
+                total_steps: num_steps,
+                step_duration,
+                eta,
+                memory: resident_memory(),
+            });
+            testv_check = steps.clone().fold(testv_check, |testv_check, step| {
+                track_testv(step, testv_check)
+            });
+
+            // the errors are those of the accumulator after the last step of the proof
+            let step = steps.end - 1;
+
+            let current_acc: Glwe<F, D, N, K> =
+                Glwe::from_slice(&step_proof.public_inputs[ranges.latest_acc.0..ranges.latest_acc.1]);
+            if step <= n && (step == 0 || debug_keys.lwe_key.is_some()) {
+                if let Some(debug_glwe_key) = debug_keys.glwe_key {
+                    info!(
+                        "Avg error: {}",
+                        current_acc.get_avg_error(debug_glwe_key, &testv_check)
+                    );
+                    info!(
+                        "Max error: {}",
+                        current_acc.get_max_error(debug_glwe_key, &testv_check)
+                    );
+                }
+            } else if step > n {
+                if let Some(debug_ksk_key) = debug_keys.ksk_key {
+                    info!(
+                        "Avg error: {}",
+                        current_acc.get_avg_error(debug_ksk_key, &testv_check)
+                    );
+                }
+                if let Some(debug_glwe_key) = debug_keys.glwe_key {
+                    info!(
+                        "Max error: {}",
+                        current_acc.get_max_error(debug_glwe_key, &testv_check)
+                    );
+                }
+            }
+
+            // with several steps per proof, the checkpoint is written after the proof that
+            // reaches each multiple of the interval
+            if let Some(checkpoint_config) = checkpoint_config {
+                let interval = checkpoint_config.interval.get();
+                if steps.end < num_steps && steps.start / interval != steps.end / interval {
+                    let checkpoint = Checkpoint {
+                        params: checkpoint_config.params.to_string(),
+                        step: steps.end,
+                        proof: step_proof,
+                    };
+                    checkpoint.save(&checkpoint_config.path)?;
+                    info!(
+                        "checkpoint at step {} written to {}",
+                        checkpoint.step,
+                        checkpoint_config.path.display()
+                    );
+                    step_proof = checkpoint.proof;
+                }
+            }
+            proof = Some(step_proof);
+        }
+
+        // a PBS has at least one step, and a checkpoint is at one of them
+        Ok(proof.expect("no step was proven"))
+    }
+}
+
+// Verifies a proof of `verified_pbs` with the cyclic circuit built with `options`: that it
+// publishes `outputs`, and that it proves the statement with digest `digest` (see
+// `PbsStatement`), so a verifier who only holds a published digest can check it without the
+// input ciphertexts, the test vector or the keys.
+pub fn verify_pbs<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F> + 'static,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    options: PbsCircuitOptions,
+    outputs: &PbsOutputs<F, D, N, K>,
+    digest: &HashOut<F>,
+    proof: &ProofWithPublicInputs<F, C, D>,
+    cd: &VerifierCircuitData<F, C, D>,
+) -> Result<()>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+{
+    ensure!(
+        *outputs == PbsOutputs::from_proof::<C, n>(options, proof),
+        "the proof is not for these output ciphertexts"
+    );
+    let ranges = PublicInputRanges::new::<n, N, K>(options);
+    let num_steps = match (&ranges.lwe_key_switch, &ranges.chain) {
+        (Some(_), _) => n + 1 + LweKeySwitchKey::<F, D, n, N, K, ELL>::NUM_CHUNKS,
+        // each PBS of a chain takes n + 2 steps, so the last step completes the last PBS, and
+        // the length of the chain is part of the statement
+        (None, Some(chain_ranges)) => {
+            let num_pbs = proof.public_inputs[chain_ranges.pbs_count].to_canonical_u64();
+            usize::try_from(num_pbs)
+                .ok()
+                .and_then(|num_pbs| num_pbs.checked_mul(n + 2))
+                .context("the proof is not for a chain of PBSs")?
+        }
+        (None, None) => n + 2,
+    };
+    verify_steps(num_steps, &ranges, digest, proof, cd)
+}
+
+// Verifies that `proof` is a proof of the cyclic circuit after `num_steps` steps, with the
+// statement digest `digest`. The output ciphertext is checked by the callers.
+fn verify_steps<F: RichField + Extendable<D>, C: GenericConfig<D, F = F> + 'static, const D: usize>(
+    num_steps: usize,
+    ranges: &PublicInputRanges,
+    digest: &HashOut<F>,
+    proof: &ProofWithPublicInputs<F, C, D>,
+    cd: &VerifierCircuitData<F, C, D>,
+) -> Result<()>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+{
+    ensure!(
+        proof.public_inputs[ranges.counter] == F::from_canonical_usize(num_steps),
+        "the proof is not for the {num_steps} steps of the PBS"
+    );
+
+    let mut timing = TimingTree::new("verify", Level::Info);
+    timed!(
+        timing,
+        "verifying Step 1",
+        cd.verify(proof.clone()).context("invalid proof")?
+    );
+    timed!(
+        timing,
+        "verifying Step 2",
+        check_cyclic_proof_verifier_data(proof, &cd.verifier_only, &cd.common)
+            .context("the proof is not for the PBS circuit")?
+    );
+    let claimed_digest = HashOut::try_from(
+        &proof.public_inputs[ranges.statement_digest.0..ranges.statement_digest.1],
+    )?;
+    timed!(
+        timing,
+        "verifying Step 3",
+        ensure!(*digest == claimed_digest, "proof is not for this PBS statement")
+    );
+    timing.print();
+
+    let counter = proof.public_inputs[ranges.counter];
+
+    info!("number of steps: {}", counter);
+
+    info!("proof size: {} bytes", proof.to_bytes().len());
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::array::from_fn;
+
+    use super::*;
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::keys::{BootstrapKey, KeySwitchKey};
+    use crate::vtfhe::crypto::poly::Poly;
+    use crate::vtfhe::crypto::rng::test_rng;
+
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::PoseidonGoldilocksConfig;
+    use plonky2::util::log2_ceil;
+    use rand::{CryptoRng, Rng, RngCore};
+
+    // the parameters of the PBS tests, with a small LWE dimension n chosen by each test
+    pub(crate) const LOGB: usize = 8;
+    pub(crate) const ELL: usize = 8;
+    pub(crate) const K: usize = 2;
+    pub(crate) const D: usize = 2;
+    pub(crate) type C = PoseidonGoldilocksConfig;
+    pub(crate) type F = <C as GenericConfig<D>>::F;
+
+    // noiseless keys of a PBS from the partial LWE key `s_lwe` of `s_to` back to `s_to`, the
+    // test vector with coefficients 0, 1, ..., N - 1, and the scaling factor `delta` of the
+    // messages, which are taken modulo 2N
+    pub(crate) struct TestFixture<const n: usize, const N: usize> {
+        pub(crate) s_to: GlweSecretKey<F, D, N, K>,
+        pub(crate) s_lwe: LweSecretKey<F, D, n>,
+        pub(crate) s_glwe: GlweSecretKey<F, D, N, K>,
+        pub(crate) bsk: BootstrapKey<F, D, n, N, K, ELL>,
+        pub(crate) ksk: KeySwitchKey<F, D, N, K, ELL>,
+        pub(crate) testv: Poly<F, D, N>,
+        pub(crate) delta: F,
+    }
+
+    pub(crate) fn test_fixture<const n: usize, const N: usize>(
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> TestFixture<n, N> {
+        let s_to = GlweSecretKey::<F, D, N, K>::generate_partial::<n>(rng);
+        let s_lwe = s_to.to_lwe_key::<n>();
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let bsk = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s_glwe, 0f64, rng);
+        let ksk = KeySwitchKey::<F, D, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_to, 0f64, rng);
+        TestFixture {
+            s_to,
+            s_lwe,
+            s_glwe,
+            bsk,
+            ksk,
+            testv: Poly {
+                coeffs: from_fn(F::from_canonical_usize),
+            },
+            delta: F::from_noncanonical_biguint(F::order() >> log2_ceil(2 * N)),
+        }
+    }
+
+    pub(super) fn check_rotation<F: RichField + Extendable<D>, const D: usize, const N: usize>(
+        in_poly: &Poly<F, D, N>,
+        out_poly: &Poly<F, D, N>,
+        mask_element: &F,
+    ) {
+        let mut shift = mask_element.to_canonical_u64() as usize;
+        shift >>= F::BITS - log2_ceil(N) - 2;
+        let carry = shift % 2;
+        shift >>= 1;
+        shift += carry;
+
+        let mut poly = in_poly;
+        let neg_poly = &in_poly.scalar_mul(&(-F::ONE));
+        if shift > N {
+            shift = shift % N;
+            poly = neg_poly;
+        }
+
+        for i in 0..N - shift {
+            assert_eq!(
+                poly.coeffs[i],
+                out_poly.coeffs[i + shift],
+                "poly not rotated correctly."
+            )
+        }
+        for i in 0..shift {
+            assert_eq!(
+                poly.coeffs[N - shift + i],
+                -out_poly.coeffs[i],
+                "poly not rotated correctly."
+            )
+        }
+    }
+
+    #[test]
+    fn test_ivc_blind_rot() {
+        const n: usize = 1;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_to, s_lwe, s_glwe, bsk, ksk, testv, delta } = test_fixture::<n, N>(rng);
+        println!("s_lwe: {:?}", s_lwe);
+        println!("testv: {:?}", testv);
+        let m = F::from_canonical_u64(rng.gen::<u64>() % (N as u64));
+        println!("message: {delta} * {m} = {}", delta * m);
+        let ct = s_lwe.encrypt(&(delta * m), 0f64, rng);
+        println!("{:?}", ct);
+        let options = PbsCircuitOptions::default();
+        let inputs = PbsInputs::new(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        let (outputs, proof, cd) = verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            options,
+            &inputs,
+            Some(&s_glwe),
+            Some(&s_lwe),
+            Some(&s_to),
+        )
+        .unwrap();
+
+        let statement = PbsStatement::<F, D, N, K>::new::<n, ELL>(&inputs);
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            options,
+            &outputs,
+            &statement.digest(),
+            &proof,
+            &cd.verifier_data(),
+        )
+        .unwrap();
+
+        // the proof is rejected for another output ciphertext or another statement
+        let out_ct = &outputs.glwes[0];
+        let mut other_out_ct: Glwe<F, D, N, K> = Glwe::from_slice(&out_ct.flatten());
+        other_out_ct.polys[0].coeffs[0] += F::ONE;
+        let other_outputs = PbsOutputs {
+            glwes: vec![other_out_ct],
+            lwe: None,
+        };
+        let err = verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            options,
+            &other_outputs,
+            &statement.digest(),
+            &proof,
+            &cd.verifier_data(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("output ciphertext"), "{err}");
+        let err = verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            options,
+            &outputs,
+            &HashOut::ZERO,
+            &proof,
+            &cd.verifier_data(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("PBS statement"), "{err}");
+        assert_eq!(*out_ct, pbs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw()));
+        let expected_pis = pbs_public_inputs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        assert_eq!(expected_pis, proof.public_inputs[..expected_pis.len()]);
+        let m_out = s_to.decrypt(out_ct);
+        println!("output ct: {:?}", out_ct);
+        println!("output poly: {:?}", m_out);
+        println!("in: {m} out: {}", m_out.coeffs[0]);
+
+        check_rotation(&testv, &m_out, &(-delta * m));
+    }
+
+    #[test]
+    fn test_pbs_statement() {
+        const n: usize = 4;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_lwe, bsk, ksk, testv, .. } = test_fixture::<n, N>(rng);
+        let ct = s_lwe.encrypt(&F::ONE, 0f64, rng);
+
+        let new_statement = |ct, testv, bsk, ksk| {
+            PbsStatement::<F, D, N, K>::new::<n, ELL>(&PbsInputs::new(ct, testv, bsk, ksk))
+        };
+        let statement = new_statement(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        assert_eq!(statement, new_statement(&ct, &testv, bsk.ggsws(), ksk.ggsw()));
+
+        // changing any part of the statement changes the digest
+        let mut other_ct = ct.clone();
+        other_ct[0] += F::ONE;
+        let other_testv = testv.left_shift(1);
+        let other_statements = [
+            new_statement(&other_ct, &testv, bsk.ggsws(), ksk.ggsw()),
+            new_statement(&ct, &other_testv, bsk.ggsws(), ksk.ggsw()),
+            new_statement(&ct, &testv, &bsk.ggsws()[1..], ksk.ggsw()),
+            new_statement(&ct, &testv, bsk.ggsws(), &bsk.ggsws()[0]),
+        ];
+        for other in other_statements {
+            assert_ne!(statement.digest(), other.digest());
+        }
+
+        // a chain of a single PBS is just a PBS, and the length of a chain is part of its
+        // statement
+        let chain_statement = |num_pbs| {
+            let inputs = PbsInputs::new(&ct, &testv, bsk.ggsws(), ksk.ggsw()).chain(num_pbs);
+            PbsStatement::new::<n, ELL>(&inputs)
+        };
+        assert_eq!(chain_statement(1), statement);
+        assert_ne!(chain_statement(2).digest(), statement.digest());
+        assert_ne!(chain_statement(2).digest(), chain_statement(3).digest());
+
+        // so is a batch of a single ciphertext, and the order of the ciphertexts of a batch is
+        // part of its statement
+        let batch_statement = |cts: Vec<&[F]>| {
+            PbsStatement::new::<n, ELL>(&PbsInputs::new_batch(cts, &testv, bsk.ggsws(), ksk.ggsw()))
+        };
+        assert_eq!(batch_statement(vec![&ct]), statement);
+        assert_ne!(batch_statement(vec![&ct, &other_ct]).digest(), statement.digest());
+        assert_ne!(
+            batch_statement(vec![&ct, &other_ct]).digest(),
+            batch_statement(vec![&other_ct, &ct]).digest()
+        );
+    }
+
+    #[test]
+    fn test_pbs_resume() {
+        const n: usize = 1;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_lwe, bsk, ksk, testv, .. } = test_fixture::<n, N>(rng);
+        let ct = s_lwe.encrypt(&F::ONE, 0f64, rng);
+
+        // with a checkpoint after every step, the last one is right before the key switch
+        let config = CheckpointConfig {
+            path: std::env::temp_dir().join(format!("vfhe_checkpoint_{}.json", std::process::id())),
+            interval: NonZeroUsize::new(1).unwrap(),
+            params: "TEST",
+        };
+        let inputs = PbsInputs::new(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        let prover = PbsProver::<F, C, D, n, N, K, ELL, LOGB>::new();
+        let (outputs, proof) = prover.prove_checkpointed(&inputs, &config).unwrap();
+        let checkpoint = Checkpoint::<F, C, D>::load(&config.path).unwrap();
+        assert_eq!(checkpoint.step, n + 1);
+
+        let (resumed_outputs, resumed_proof) = prover.resume(&inputs, &config).unwrap();
+        assert_eq!(resumed_outputs, outputs);
+        assert_eq!(resumed_proof.public_inputs, proof.public_inputs);
+        let statement = PbsStatement::<F, D, N, K>::new::<n, ELL>(&inputs);
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            PbsCircuitOptions::default(),
+            &resumed_outputs,
+            &statement.digest(),
+            &resumed_proof,
+            &prover.circuit_data.verifier_data(),
+        )
+        .unwrap();
+
+        // the checkpoint cannot be used to prove the PBS of another ciphertext
+        let mut other_ct = ct.clone();
+        other_ct[0] += F::ONE;
+        let other_inputs = PbsInputs::new(&other_ct, &testv, bsk.ggsws(), ksk.ggsw());
+        let err = prover.resume(&other_inputs, &config).unwrap_err();
+        assert!(err.to_string().contains("different PBS"), "{err}");
+
+        // nor with the keys of another parameter set
+        let other_config = CheckpointConfig {
+            params: "OTHER_TEST",
+            ..config
+        };
+        let err = prover.resume(&inputs, &other_config).unwrap_err();
+        assert!(err.to_string().contains("parameter set TEST"), "{err}");
+        fs::remove_file(&other_config.path).unwrap();
+    }
+
+    #[test]
+    fn test_pbs_prover() {
+        const n: usize = 1;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_lwe, bsk, ksk, testv, delta, .. } = test_fixture::<n, N>(rng);
+        let cts: Vec<Vec<F>> = (0..2)
+            .map(|m| s_lwe.encrypt(&(delta * F::from_canonical_usize(m)), 0f64, rng))
+            .collect();
+
+        // the second ciphertext is proven with a prover loaded from the bytes of the first one
+        let prover = PbsProver::<F, C, D, n, N, K, ELL, LOGB>::new();
+        let loaded_prover =
+            PbsProver::<F, C, D, n, N, K, ELL, LOGB>::from_bytes(&prover.to_bytes().unwrap())
+                .unwrap();
+        let cd = prover.circuit_data.verifier_data();
+        for (ct, prover) in cts.iter().zip([&prover, &loaded_prover]) {
+            let inputs = PbsInputs::new(ct, &testv, bsk.ggsws(), ksk.ggsw());
+            let (outputs, proof) = prover.prove(&inputs).unwrap();
+            let digest = PbsStatement::<F, D, N, K>::new::<n, ELL>(&inputs).digest();
+            verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+                PbsCircuitOptions::default(),
+                &outputs,
+                &digest,
+                &proof,
+                &cd,
+            )
+            .unwrap();
+            assert_eq!(
+                outputs.into_glwe().unwrap(),
+                pbs::<F, D, n, N, K, ELL, LOGB>(ct, &testv, bsk.ggsws(), ksk.ggsw())
+            );
+        }
+
+        // the circuit only proves PBSs with the parameters it was built for
+        let err = PbsProver::<F, C, D, 2, N, K, ELL, LOGB>::from_bytes(&prover.to_bytes().unwrap())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("built for the parameters"), "{err}");
+    }
+
+    #[test]
+    fn test_pbs_steps_per_proof() {
+        const n: usize = 1;
+        // a smaller ring keeps the circuit with several CMUXs small
+        const N: usize = 64;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_lwe, bsk, ksk, testv, delta, .. } = test_fixture::<n, N>(rng);
+        let ct = s_lwe.encrypt(&(delta * F::ONE), 0f64, rng);
+
+        // the n + 2 = 3 steps take two proofs, the second of which skips its last step
+        let prover = PbsProver::<F, C, D, n, N, K, ELL, LOGB, 2>::new();
+        let inputs = PbsInputs::new(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        let (outputs, proof) = prover.prove(&inputs).unwrap();
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            PbsCircuitOptions::default(),
+            &outputs,
+            &PbsStatement::new::<n, ELL>(&inputs).digest(),
+            &proof,
+            &prover.circuit_data.verifier_data(),
+        )
+        .unwrap();
+        assert_eq!(
+            outputs.into_glwe().unwrap(),
+            pbs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw())
+        );
+
+        let err = PbsProver::<F, C, D, n, N, K, ELL, LOGB>::from_bytes(&prover.to_bytes().unwrap())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("built for the parameters"), "{err}");
+    }
+
+    #[test]
+    fn test_pbs_prove_many() {
+        const n: usize = 1;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_lwe, bsk, ksk, delta, .. } = test_fixture::<n, N>(rng);
+        // each job has its own ciphertext and test vector
+        let jobs: Vec<PbsJob<F, D, N>> = (0..2)
+            .map(|m| PbsJob {
+                ct: s_lwe.encrypt(&(delta * F::from_canonical_usize(m)), 0f64, rng),
+                testv: Poly {
+                    coeffs: from_fn(|i| F::from_canonical_usize(i * (m + 1))),
+                },
+            })
+            .collect();
+
+        let prover = PbsProver::<F, C, D, n, N, K, ELL, LOGB>::new();
+        let proofs = Mutex::new(Vec::new());
+        prover
+            .prove_many(
+                jobs.iter(),
+                |job| {
+                    Ok(PbsJob {
+                        ct: job.ct.clone(),
+                        testv: job.testv.clone(),
+                    })
+                },
+                bsk.ggsws(),
+                ksk.ggsw(),
+                NonZeroUsize::new(2).unwrap(),
+                |index, out_ct, proof| {
+                    proofs.lock().unwrap().push((index, out_ct, proof));
+                    Ok(())
+                },
+            )
+            .unwrap();
+        let mut proofs = proofs.into_inner().unwrap();
+        proofs.sort_by_key(|(index, _, _)| *index);
+        assert_eq!(proofs.len(), jobs.len());
+        let cd = prover.circuit_data.verifier_data();
+        for (job, (_, out_ct, proof)) in jobs.iter().zip(&proofs) {
+            let expected = pbs::<F, D, n, N, K, ELL, LOGB>(&job.ct, &job.testv, bsk.ggsws(), ksk.ggsw());
+            assert_eq!(*out_ct, expected);
+            let inputs = PbsInputs::new(&job.ct, &job.testv, bsk.ggsws(), ksk.ggsw());
+            let outputs = PbsOutputs {
+                glwes: vec![expected],
+                lwe: None,
+            };
+            verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+                PbsCircuitOptions::default(),
+                &outputs,
+                &PbsStatement::new::<n, ELL>(&inputs).digest(),
+                proof,
+                &cd,
+            )
+            .unwrap();
+        }
+
+        // a job that cannot be read fails the whole run
+        let err = prover
+            .prove_many(
+                once(()),
+                |_| Err(anyhow!("unreadable inputs")),
+                bsk.ggsws(),
+                ksk.ggsw(),
+                NonZeroUsize::new(2).unwrap(),
+                |_, _, _| Ok(()),
+            )
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("job 0 failed: unreadable inputs"), "{err:#}");
+        // and so does a malformed ciphertext, before anything is proven
+        let short_job = PbsJob {
+            ct: jobs[0].ct[..n].to_vec(),
+            testv: jobs[0].testv.clone(),
+        };
+        let err = prover
+            .prove_many(
+                once(short_job),
+                Ok,
+                bsk.ggsws(),
+                ksk.ggsw(),
+                NonZeroUsize::new(2).unwrap(),
+                |_, _, _| Ok(()),
+            )
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("job 0 failed: expected an LWE ciphertext of 2 elements, found 1"),
+            "{err:#}"
+        );
+    }
+}
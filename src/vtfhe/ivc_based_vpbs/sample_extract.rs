@@ -0,0 +1,77 @@
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::vtfhe::glwe_ct::GlweCt;
+
+// Publishes the sample extraction of the accumulator after the step, an LWE ciphertext of
+// dimension n under the partial output key (see `PbsCircuitOptions::extract_lwe`). It is only
+// meaningful in the last step, after the key switch.
+pub(super) fn register_sample_extraction<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    acc_out: &GlweCt<N, K>,
+) {
+    let lwe_out = acc_out.partial_sample_extract(builder, n);
+    builder.register_public_inputs(&lwe_out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{test_fixture, TestFixture, C, D, ELL, F, K, LOGB};
+    use super::super::{
+        pbs_public_inputs, verified_pbs, verify_pbs, PbsCircuitOptions, PbsInputs, PbsOutputs,
+        PbsStatement,
+    };
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::pbs;
+    use crate::vtfhe::crypto::rng::test_rng;
+
+    use plonky2::field::types::Field;
+
+    #[test]
+    fn test_ivc_pbs_extracted() {
+        const n: usize = 1;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_to, s_lwe, bsk, ksk, testv, delta, .. } = test_fixture::<n, N>(rng);
+        let ct = s_lwe.encrypt(&(delta * F::ONE), 0f64, rng);
+
+        let options = PbsCircuitOptions {
+            extract_lwe: true,
+            ..Default::default()
+        };
+        let inputs = PbsInputs::new(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        let (outputs, proof, cd) =
+            verified_pbs::<F, C, D, n, N, K, ELL, LOGB>(options, &inputs, None, None, None).unwrap();
+        let out_glwe = pbs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        let out_ct = outputs.lwe.as_ref().unwrap();
+        assert_eq!(*out_ct, out_glwe.partial_sample_extract(n));
+        assert_eq!(s_lwe.decrypt(out_ct), s_to.decrypt(&out_glwe).coeffs[0]);
+        assert_eq!(outputs.glwes[0], out_glwe);
+        let digest = PbsStatement::new::<n, ELL>(&inputs).digest();
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(options, &outputs, &digest, &proof, &cd.verifier_data())
+            .unwrap();
+
+        // the extracted ciphertext is published after the public inputs of a PBS without it
+        let expected_pis = pbs_public_inputs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw());
+        assert_eq!(expected_pis, proof.public_inputs[..expected_pis.len()]);
+        let glwe_outputs = PbsOutputs {
+            glwes: vec![out_glwe],
+            lwe: None,
+        };
+        verify_pbs::<F, C, D, n, N, K, ELL, LOGB>(
+            PbsCircuitOptions::default(),
+            &glwe_outputs,
+            &digest,
+            &proof,
+            &cd.verifier_data(),
+        )
+        .unwrap();
+    }
+}
@@ -15,9 +15,11 @@ use crate::vec_arithmetic::{scalar_mul, vec_add_many};
 
 use super::glwe_poly::decompose;
 
+// ELL LWE ciphertexts of dimension n (n + 1 targets each, the body last) of a message times
+// the powers of B of the top ELL limbs, see `LweKeySwitchKey`
 #[derive(Debug)]
 pub struct LevCt<const n: usize, const ELL: usize> {
-    pub lwe_cts: [[Target; n]; ELL],
+    pub lwe_cts: [Vec<Target>; ELL],
 }
 
 impl<const n: usize, const ELL: usize> LevCt<n, ELL> {
@@ -25,15 +27,23 @@ impl<const n: usize, const ELL: usize> LevCt<n, ELL> {
         cb: &mut CircuitBuilder<F, D>,
     ) -> Self {
         LevCt {
-            lwe_cts: from_fn(|_| from_fn(|_| cb.add_virtual_target())),
+            lwe_cts: from_fn(|_| cb.add_virtual_targets(n + 1)),
+        }
+    }
+
+    pub fn new_from_targets(targets: &[Target]) -> Self {
+        assert_eq!(
+            targets.len(),
+            Self::num_targets(),
+            "Incorrect number of targets to construct LevCt."
+        );
+        LevCt {
+            lwe_cts: from_fn(|i| targets[i * (n + 1)..(i + 1) * (n + 1)].to_vec()),
         }
     }
 
     pub fn flatten(&self) -> Vec<Target> {
-        self.lwe_cts
-            .iter()
-            .flat_map(|&lwe_ct| lwe_ct.into_iter())
-            .collect()
+        self.lwe_cts.iter().flatten().copied().collect()
     }
 
     pub fn register<F: RichField + Extendable<D>, const D: usize>(
@@ -56,10 +66,22 @@ impl<const n: usize, const ELL: usize> LevCt<n, ELL> {
         }
     }
 
+    pub fn assign<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        pw: &mut PartialWitness<F>,
+        lev: &[Vec<F>; ELL],
+    ) {
+        for (x, y) in self.lwe_cts.iter().zip(lev.iter()) {
+            pw.set_target_arr(x, y);
+        }
+    }
+
     pub fn num_targets() -> usize {
-        n * ELL
+        (n + 1) * ELL
     }
 
+    // the product of the message with `mask`, decomposed into its top ELL limbs like in
+    // `GlevCt::mul`
     pub fn mul<F: RichField + Extendable<D>, const D: usize, const LOGB: usize>(
         &self,
         cb: &mut CircuitBuilder<F, D>,
@@ -67,10 +89,10 @@ impl<const n: usize, const ELL: usize> LevCt<n, ELL> {
     ) -> Vec<Target> {
         let num_limbs = ceil_div_usize(F::BITS, LOGB);
         let limbs = decompose::<F, D, LOGB>(cb, mask, num_limbs);
-        let summands = limbs
-            .into_iter()
+        let summands = limbs[num_limbs - ELL..]
+            .iter()
             .zip(self.lwe_cts.iter())
-            .map(|(limb, lwe)| scalar_mul(cb, limb, lwe))
+            .map(|(&limb, lwe)| scalar_mul(cb, limb, lwe))
             .collect();
         vec_add_many(cb, &summands)
     }
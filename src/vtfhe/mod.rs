@@ -24,10 +24,13 @@ pub mod glwe_ct;
 pub mod glwe_poly;
 pub mod ivc_based_vpbs;
 pub mod lev_ct;
+pub mod pcd_based_vpbs;
+pub mod progress;
 
-// the key switch incorporates the sample extraction, hence glwe -> lwe
-// we also assume the ksk is set up nicely so that sample extraction is
-// literally reading off the coefficients (no rearranging/negating)
+// The LWE-to-LWE key switch of the sample extraction of `glwe_ct` (under the flattened GLWE
+// key) to an LWE key of dimension n. The key switch incorporates the sample extraction: the KSK
+// has a LevCt per coefficient of the mask, set up so that sample extraction is literally
+// reading off the coefficients (no rearranging/negating), see `LweKeySwitchKey`.
 pub fn key_switch<
     F: RichField + Extendable<D>,
     const D: usize,
@@ -39,18 +42,36 @@ pub fn key_switch<
 >(
     cb: &mut CircuitBuilder<F, D>,
     glwe_ct: &GlweCt<N, K>,
-    ksk: &[LevCt<n, ELL>; N],
+    ksk: &[LevCt<n, ELL>],
 ) -> Vec<Target> {
-    let summands = glwe_ct.flatten()[GlweCt::<N, K>::num_targets() - N..]
+    assert_eq!(ksk.len(), (K - 1) * N, "expected a LevCt per coefficient of the mask");
+    let mut init = vec![cb.zero(); n];
+    init.push(glwe_ct.polys[K - 1].coeffs[0]);
+    key_switch_chunk::<F, D, LOGB, n, ELL>(cb, &init, &glwe_ct.flatten()[..(K - 1) * N], ksk)
+}
+
+// Adds the products of mask coefficients with their LevCts to an LWE ciphertext, so that the
+// key switch can be split over several chunks of the mask. The KSK encrypts -s_i instead of
+// s_i, so we can simply add here.
+pub fn key_switch_chunk<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const LOGB: usize,
+    const n: usize,
+    const ELL: usize,
+>(
+    cb: &mut CircuitBuilder<F, D>,
+    lwe_ct: &[Target],
+    mask: &[Target],
+    ksk: &[LevCt<n, ELL>],
+) -> Vec<Target> {
+    let summands = mask
         .iter()
         .zip(ksk.iter())
         .map(|(&mask, lev_ct)| lev_ct.mul::<F, D, LOGB>(cb, mask))
         .collect();
     let sum = vec_add_many(cb, &summands);
-    let mut init = vec![cb.zero(); n - 1];
-    init.push(*glwe_ct.polys.last().unwrap().coeffs.first().unwrap());
-    // we assume the KSK encrypts -s_i instead of s_i so we can simply add here
-    vec_add(cb, &init, &sum)
+    vec_add(cb, lwe_ct, &sum)
 }
 
 pub fn poly_select<F: RichField + Extendable<D>, const D: usize, const N: usize>(
@@ -142,14 +163,16 @@ mod tests {
     use crate::vtfhe::crypto::ggsw::Ggsw;
     use crate::vtfhe::crypto::glwe::Glwe;
     use crate::vtfhe::crypto::poly::Poly;
+    use crate::vtfhe::crypto::rng::test_rng;
 
     use plonky2::field::types::{Field, Sample};
     use plonky2::iop::witness::{PartialWitness, WitnessWrite};
     use plonky2::plonk::circuit_data::CircuitConfig;
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
-    use rand::random;
-    use tests::crypto::compute_bsk;
-    use tests::crypto::lwe::{encrypt, key_gen};
+    use rand::Rng;
+    use tests::crypto::blind_rotate;
+    use tests::crypto::keys::{BootstrapKey, GlweSecretKey, LweKeySwitchKey, LweSecretKey};
+    use tests::crypto::lwe::mod_switch_element;
 
     fn check_rotation<F: RichField + Extendable<D>, const D: usize, const N: usize>(
         in_poly: &Poly<F, D, N>,
@@ -201,8 +224,9 @@ mod tests {
         poly.register(&mut builder);
         builder.register_public_input(mask_element);
 
-        let poly_vals = Poly::<F, D, N>::rand();
-        let mask_val = F::rand();
+        let rng = &mut test_rng();
+        let poly_vals = Poly::<F, D, N>::rand(rng);
+        let mask_val = F::sample(rng);
         poly.assign(&mut pw, &poly_vals);
         pw.set_target(mask_element, mask_val);
 
@@ -216,6 +240,7 @@ mod tests {
         let out_poly = Poly::<F, D, N>::from_slice(&out_poly_sclice);
 
         check_rotation(&poly_vals, &out_poly, &mask_val);
+        assert_eq!(out_poly, poly_vals.rotate(mod_switch_element::<F, D>(mask_val, N)));
         let _ = data.verify(proof).unwrap();
     }
 
@@ -240,16 +265,17 @@ mod tests {
         ggsw.register(&mut builder);
         builder.register_public_input(mask_element);
 
-        let s = Glwe::<F, D, N, K>::key_gen();
-        let bit = F::from_canonical_u64(rand::random::<u64>() % 2);
+        let rng = &mut test_rng();
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
+        let bit = F::from_canonical_u64(rng.gen::<u64>() % 2);
         let m_glwe = Poly::<F, D, N> {
             coeffs: from_fn(|i| F::from_canonical_usize(i)),
         };
 
         let m_ggsw = Poly::constant(&bit);
-        let ct_glwe = Glwe::<F, D, N, K>::encrypt(&s, &m_glwe, 0f64);
-        let ct_ggsw = Ggsw::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &m_ggsw, 0f64).ntt_forward();
-        let ai = F::rand();
+        let ct_glwe = Glwe::<F, D, N, K>::encrypt(&s, &m_glwe, 0f64, rng);
+        let ct_ggsw = Ggsw::<F, D, N, K, ELL>::encrypt::<LOGB>(&s, &m_ggsw, 0f64, rng).ntt_forward();
+        let ai = F::sample(rng);
 
         glwe.assign(&mut pw, &ct_glwe);
         ggsw.assign(&mut pw, &ct_ggsw);
@@ -267,6 +293,10 @@ mod tests {
         let start = GlweCt::<N, K>::num_targets() + GgswCt::<N, K, ELL>::num_targets() + 1;
         let out_glwe_slice = &proof.public_inputs[start..start + GlweCt::<N, K>::num_targets()];
         let out_glwe = Glwe::<F, D, N, K>::from_slice(&out_glwe_slice);
+        assert_eq!(
+            out_glwe,
+            crypto::blind_rotation_step::<F, D, LOGB, N, K, ELL>(&ct_glwe, &ct_ggsw, ai)
+        );
         let m_out = out_glwe.decrypt(&s);
         println!("a: {ai}");
         println!("m_in: {:?}", m_glwe);
@@ -319,8 +349,9 @@ mod tests {
             builder.register_public_input(*element);
         }
 
-        let s = Glwe::<F, D, N, K>::key_gen();
-        let s_lwe = key_gen::<F, D, n>();
+        let rng = &mut test_rng();
+        let s = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let s_lwe = LweSecretKey::<F, D, n>::generate(rng);
         println!("lwe key: {:?}", s_lwe);
         let testv = Poly::<F, D, N> {
             coeffs: from_fn(|i| F::from_canonical_usize(i)),
@@ -329,10 +360,10 @@ mod tests {
         println!("init acc: {:?}", testv);
 
         let test_ct = Glwe::trivial_ct(testv.clone());
-        let bsk_vals = compute_bsk::<F, D, N, K, ELL, LOGB>(&s_lwe, &s, 0f64);
-        let m = F::from_canonical_u64(random::<u64>() % (N as u64));
+        let bsk_vals = BootstrapKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_lwe, &s, 0f64, rng).into_ggsws();
+        let m = F::from_canonical_u64(rng.gen::<u64>() % (N as u64));
         let delta = F::from_noncanonical_biguint(F::order() >> log2_ceil(2 * N));
-        let lwe_vals = encrypt::<F, D, n>(&s_lwe, &(delta * m), 0f64);
+        let lwe_vals = s_lwe.encrypt(&(delta * m), 0f64, rng);
         println!("m: {m}, -Delta * m: {}", -delta * m);
         println!("lwe_ct: {:?}", lwe_vals);
 
@@ -352,7 +383,11 @@ mod tests {
 
         let out_glwe_slice = &proof.public_inputs[..GlweCt::<N, K>::num_targets()];
         let out_glwe = Glwe::<F, D, N, K>::from_slice(&out_glwe_slice);
-        let m_out = out_glwe.decrypt(&s);
+        assert_eq!(
+            out_glwe,
+            blind_rotate::<F, D, n, N, K, ELL, LOGB>(&lwe_vals, &testv, &bsk_vals)
+        );
+        let m_out = s.decrypt(&out_glwe);
         check_rotation(&testv, &m_out, &(-delta * m));
     }
 
@@ -380,11 +415,12 @@ mod tests {
         let z = glwe_select(&mut builder, counter_is_zero, &glwe1, &glwe2);
         z.register(&mut builder);
 
-        let poly1 = Poly::<F, D, N>::rand();
-        let poly2 = Poly::<F, D, N>::rand();
-        let s = Glwe::<F, D, N, K>::key_gen();
-        let glwe_ct1 = Glwe::encrypt(&s, &poly1, 0f64);
-        let glwe_ct2 = Glwe::encrypt(&s, &poly2, 0f64);
+        let rng = &mut test_rng();
+        let poly1 = Poly::<F, D, N>::rand(rng);
+        let poly2 = Poly::<F, D, N>::rand(rng);
+        let s = Glwe::<F, D, N, K>::key_gen(rng);
+        let glwe_ct1 = Glwe::encrypt(&s, &poly1, 0f64, rng);
+        let glwe_ct2 = Glwe::encrypt(&s, &poly2, 0f64, rng);
         let counter_val = F::TWO;
 
         glwe1.assign(&mut pw, &glwe_ct1);
@@ -407,7 +443,7 @@ mod tests {
     #[test]
     fn test_key_switch() {
         const LOGB: usize = 8;
-        const ELL: usize = 2;
+        const ELL: usize = 8;
         const n: usize = 2;
 
         const K: usize = 2;
@@ -421,22 +457,34 @@ mod tests {
         let mut pw = PartialWitness::new();
 
         let glwe: GlweCt<N, K> = GlweCt::new_from_builder(&mut builder);
-        let ksk: [LevCt<n, ELL>; N] = from_fn(|_| LevCt::new_from_builder(&mut builder));
-
-        glwe.register(&mut builder);
-        glwe.set_to_random::<F, D>(&mut pw);
-
-        for lev in &ksk {
-            lev.register(&mut builder);
-            lev.set_to_random::<F, D>(&mut pw);
-        }
+        let ksk: Vec<LevCt<n, ELL>> = (0..(K - 1) * N)
+            .map(|_| LevCt::new_from_builder(&mut builder))
+            .collect();
 
         let z = key_switch::<F, D, LOGB, n, ELL, N, K>(&mut builder, &glwe, &ksk);
         builder.register_public_inputs(&z);
 
+        let rng = &mut test_rng();
+        let s_glwe = GlweSecretKey::<F, D, N, K>::generate(rng);
+        let s_lwe = LweSecretKey::<F, D, n>::generate(rng);
+        let ksk_vals = LweKeySwitchKey::<F, D, n, N, K, ELL>::generate::<LOGB>(&s_glwe, &s_lwe, 0f64, rng);
+        let m = Poly::<F, D, N>::rand(rng);
+        let glwe_val = s_glwe.encrypt(&m, 0f64, rng);
+
+        glwe.assign(&mut pw, &glwe_val);
+        for (lev, lev_val) in ksk.iter().zip(ksk_vals.levs()) {
+            lev.assign::<F, D>(&mut pw, lev_val);
+        }
+
         let data = builder.build::<C>();
         let proof = data.prove(pw).unwrap();
-
-        let _ = data.verify(proof).unwrap();
+        data.verify(proof.clone()).unwrap();
+
+        // with all limbs and no noise the key switch is exact
+        assert_eq!(
+            proof.public_inputs,
+            crypto::key_switch::<F, D, n, N, K, ELL, LOGB>(&glwe_val, &ksk_vals)
+        );
+        assert_eq!(s_lwe.decrypt(&proof.public_inputs), m.coeffs[0]);
     }
 }
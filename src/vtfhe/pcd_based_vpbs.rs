@@ -0,0 +1,681 @@
+use anyhow::{ensure, Context, Result};
+use log::{info, Level};
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField, NUM_HASH_OUT_ELTS};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{
+    CircuitConfig, CircuitData, CommonCircuitData, VerifierCircuitData, VerifierCircuitTarget,
+};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+use plonky2::plonk::prover::prove;
+use plonky2::recursion::cyclic_recursion::check_cyclic_proof_verifier_data;
+use plonky2::recursion::dummy_circuit::cyclic_base_proof;
+use plonky2::util::timing::TimingTree;
+use std::iter::once;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+use super::crypto::ggsw::Ggsw;
+use super::crypto::glwe::Glwe;
+use super::crypto::poly::Poly;
+use super::crypto::{blind_rotation_step, rotate_glwe};
+use super::ggsw_ct::GgswCt;
+use super::glwe_ct::GlweCt;
+use super::glwe_select;
+use super::ivc_based_vpbs::{
+    build_cyclic_circuit_data, ggsw_digest, glwe_pbs_step, hash_select, step_masks,
+    try_for_each_parallel, PbsInputs, PbsStatement,
+};
+
+// the output ciphertext of a PBS, its proof and the node circuit
+pub type PbsTreeOutput<F, C, const D: usize, const N: usize, const K: usize> = (
+    Glwe<F, D, N, K>,
+    ProofWithPublicInputs<F, C, D>,
+    CircuitData<F, C, D>,
+);
+
+// the positions of the public inputs of the node circuit (before the verifier data): the
+// steps [start, end) proven by a node, and the accumulator and hash chains before and after them
+struct NodeRanges {
+    acc_init: (usize, usize),
+    start: (usize, usize),
+    end: (usize, usize),
+    acc_in: (usize, usize),
+    acc_out: (usize, usize),
+    bsk_hash_in: (usize, usize),
+    bsk_hash_out: (usize, usize),
+    lwe_hash_in: (usize, usize),
+    lwe_hash_out: (usize, usize),
+    statement_digest: (usize, usize),
+}
+
+impl NodeRanges {
+    fn new<const N: usize, const K: usize>() -> Self {
+        let mut len = 0;
+        let mut next = |size: usize| {
+            len += size;
+            (len - size, len)
+        };
+        let glwe_len = GlweCt::<N, K>::num_targets();
+        NodeRanges {
+            acc_init: next(glwe_len),
+            start: next(1),
+            end: next(1),
+            acc_in: next(glwe_len),
+            acc_out: next(glwe_len),
+            bsk_hash_in: next(NUM_HASH_OUT_ELTS),
+            bsk_hash_out: next(NUM_HASH_OUT_ELTS),
+            lwe_hash_in: next(NUM_HASH_OUT_ELTS),
+            lwe_hash_out: next(NUM_HASH_OUT_ELTS),
+            statement_digest: next(NUM_HASH_OUT_ELTS),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.statement_digest.1
+    }
+
+    // the public inputs a node takes from the state before its first step
+    fn inputs(&self) -> [(usize, usize); 5] {
+        [
+            self.acc_init,
+            self.start,
+            self.acc_in,
+            self.bsk_hash_in,
+            self.lwe_hash_in,
+        ]
+    }
+}
+
+// the targets of the node circuit that are assigned when proving a node
+struct NodeTargets<const D: usize, const N: usize, const K: usize, const ELL: usize> {
+    node_pis: Vec<Target>,
+    is_leaf: BoolTarget,
+    // the GGSW and the LWE element consumed by each step of a leaf
+    ggsws: Vec<GgswCt<N, K, ELL>>,
+    mask_elements: Vec<Target>,
+    left_proof_with_pis: ProofWithPublicInputsTarget<D>,
+    right_proof_with_pis: ProofWithPublicInputsTarget<D>,
+    verifier_data: VerifierCircuitTarget,
+}
+
+// the accumulator and the BSK and LWE hash chains between two steps of a PBS
+struct StepState<F: RichField + Extendable<D>, const D: usize, const N: usize, const K: usize> {
+    acc: Glwe<F, D, N, K>,
+    bsk_hash: HashOut<F>,
+    lwe_hash: HashOut<F>,
+}
+
+// The state before each step of the PBS and after the last one, computed natively in the same
+// way as the steps of the circuit, so that chunks of steps can be proven independently
+fn step_states<
+    F: RichField + Extendable<D>,
+    const D: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    acc_init: &[F],
+    step_ggsws: &[&Ggsw<F, D, N, K, ELL>],
+    mask_elements: &[F],
+) -> Vec<StepState<F, D, N, K>> {
+    let key_switch_step = step_ggsws.len() - 1;
+    let mut states = vec![StepState {
+        acc: Glwe::from_slice(acc_init),
+        bsk_hash: HashOut::ZERO,
+        lwe_hash: HashOut::ZERO,
+    }];
+    for (step, (ggsw, &mask_element)) in step_ggsws.iter().zip(mask_elements).enumerate() {
+        let state = states.last().unwrap();
+        let acc = match step {
+            // the rotation by the body, which is negated
+            0 => rotate_glwe(&state.acc, -mask_element),
+            step if step == key_switch_step => ggsw.external_product::<LOGB>(&state.acc),
+            _ => blind_rotation_step::<F, D, LOGB, N, K, ELL>(&state.acc, ggsw, mask_element),
+        };
+        let bsk_data: Vec<F> = state
+            .bsk_hash
+            .elements
+            .into_iter()
+            .chain(ggsw_digest(ggsw).elements)
+            .collect();
+        let lwe_data: Vec<F> = state.lwe_hash.elements.into_iter().chain(once(mask_element)).collect();
+        states.push(StepState {
+            acc,
+            bsk_hash: PoseidonHash::hash_no_pad(&bsk_data),
+            lwe_hash: PoseidonHash::hash_no_pad(&lwe_data),
+        });
+    }
+    states
+}
+
+// constrains `x == y` unless `condition` holds
+fn connect_unless<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    condition: BoolTarget,
+    x: Target,
+    y: Target,
+) {
+    let y = builder.select(condition, x, y);
+    builder.connect(x, y);
+}
+
+// Maps `f` over `items` with `try_for_each_parallel`; the results are in the order of the items
+fn try_parallel_map<T: Send, R: Send>(
+    items: Vec<T>,
+    num_workers: NonZeroUsize,
+    f: impl Fn(T) -> Result<R> + Sync,
+) -> Result<Vec<R>> {
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+    try_for_each_parallel(items.into_iter(), num_workers, |index, item| {
+        let result = f(item)?;
+        results.lock().unwrap().push((index, result));
+        Ok(())
+    })?;
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+// An alternative to the linear IVC of `PbsProver`, whose n + 2 steps can only be proven one
+// after the other. The steps of the PBS are computed natively first, which gives the state
+// before each step, and are split into chunks of `chunk_size` consecutive steps. Each chunk
+// (a leaf) is proven independently from the state before its first step, and the proofs are
+// merged pairwise in a binary tree, each merge checking that its right child continues from
+// the accumulator and the hash chains where its left child stops. The leaves and the merges of
+// a level of the tree are proven in parallel.
+//
+// Leaves and merges are proofs of the same cyclic circuit (a node), which either proves a chunk
+// of steps or verifies two proofs of itself. The root proves the same `PbsStatement` as the
+// last proof of `PbsProver`, see `verify_pbs_tree_digest`.
+pub struct PbsTreeProver<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+> {
+    pub circuit_data: CircuitData<F, C, D>,
+    targets: NodeTargets<D, N, K, ELL>,
+    chunk_size: usize,
+}
+
+impl<
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        const D: usize,
+        const n: usize,
+        const N: usize,
+        const K: usize,
+        const ELL: usize,
+        const LOGB: usize,
+    > PbsTreeProver<F, C, D, n, N, K, ELL, LOGB>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+    C: 'static,
+{
+    pub fn new(chunk_size: NonZeroUsize) -> Self {
+        info!(
+            "Parameters: n={n}, N={N}, k={}, logB={LOGB}, ell={ELL}, chunks of {chunk_size} steps",
+            K - 1
+        );
+        let chunk_size = chunk_size.get();
+        let (circuit_data, targets) = build_cyclic_circuit_data::<F, C, D, _>(|common_data| {
+            Self::build_node_circuit(common_data, chunk_size)
+        });
+        PbsTreeProver {
+            circuit_data,
+            targets,
+            chunk_size,
+        }
+    }
+
+    // builds the node circuit, which verifies proofs with the given common data (up to the
+    // number of public inputs, which is set here)
+    fn build_node_circuit(
+        mut common_data: CommonCircuitData<F, D>,
+        chunk_size: usize,
+    ) -> (CircuitBuilder<F, D>, NodeTargets<D, N, K, ELL>) {
+        let config = CircuitConfig::standard_recursion_config();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+        let zero = builder.zero();
+        let ranges = NodeRanges::new::<N, K>();
+        let node_pis: Vec<Target> = (0..ranges.len())
+            .map(|_| builder.add_virtual_public_input())
+            .collect();
+        let pis = |range: (usize, usize)| &node_pis[range.0..range.1];
+        let acc_init = GlweCt::<N, K>::new_from_targets(pis(ranges.acc_init));
+        let start = node_pis[ranges.start.0];
+        let hash_in = |range| HashOutTarget::try_from(pis(range)).unwrap();
+
+        // A leaf proves the steps of a chunk in the same way as the cyclic circuit of `PbsProver`,
+        // with the step computed from `start`. The steps after the key switch in the last chunk
+        // leave the state unchanged, and are not counted in `end`.
+        let key_switch_step = builder.constant(F::from_canonical_usize(n + 1));
+        let mut acc = GlweCt::<N, K>::new_from_targets(pis(ranges.acc_in));
+        let mut bsk_hash = hash_in(ranges.bsk_hash_in);
+        let mut lwe_hash = hash_in(ranges.lwe_hash_in);
+        let mut statement_digest = HashOutTarget::from([zero; NUM_HASH_OUT_ELTS]);
+        let mut active = builder._true();
+        let mut num_active = zero;
+        let mut ggsws = Vec::with_capacity(chunk_size);
+        let mut mask_elements = Vec::with_capacity(chunk_size);
+        for i in 0..chunk_size {
+            let step = builder.add_const(start, F::from_canonical_usize(i));
+            let first_step = builder.is_equal(step, zero);
+            let last_step = builder.is_equal(step, key_switch_step);
+            let ggsw = GgswCt::<N, K, ELL>::new_from_builder(&mut builder);
+            let mask_element = builder.add_virtual_target();
+            let step_acc = glwe_pbs_step::<F, D, LOGB, N, K, ELL>(
+                &mut builder,
+                &ggsw,
+                &acc,
+                mask_element,
+                first_step,
+                last_step,
+            );
+            let ggsw_digest = builder.hash_n_to_hash_no_pad::<PoseidonHash>(ggsw.flatten());
+            let step_bsk_hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+                bsk_hash.elements.into_iter().chain(ggsw_digest.elements).collect(),
+            );
+            let step_lwe_hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+                lwe_hash.elements.into_iter().chain(once(mask_element)).collect(),
+            );
+            // see `PbsStatement`: in the last step, the BSK hash chain holds the hash of the BSK,
+            // and the GGSW is the KSK
+            let step_digest = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+                step_lwe_hash
+                    .elements
+                    .into_iter()
+                    .chain(acc_init.flatten())
+                    .chain(bsk_hash.elements)
+                    .chain(ggsw_digest.elements)
+                    .collect(),
+            );
+            statement_digest = hash_select(&mut builder, last_step, step_digest, statement_digest);
+
+            acc = glwe_select(&mut builder, active, &step_acc, &acc);
+            bsk_hash = hash_select(&mut builder, active, step_bsk_hash, bsk_hash);
+            lwe_hash = hash_select(&mut builder, active, step_lwe_hash, lwe_hash);
+            num_active = builder.add(num_active, active.target);
+            let not_last_step = builder.not(last_step);
+            active = builder.and(active, not_last_step);
+            ggsws.push(ggsw);
+            mask_elements.push(mask_element);
+        }
+        let leaf_end = builder.add(start, num_active);
+
+        let verifier_data = builder.add_verifier_data_public_inputs();
+        common_data.num_public_inputs = builder.num_public_inputs();
+
+        let is_leaf = builder.add_virtual_bool_target_safe();
+        let is_merge = builder.not(is_leaf);
+        let left_proof_with_pis = builder.add_virtual_proof_with_pis(&common_data);
+        let right_proof_with_pis = builder.add_virtual_proof_with_pis(&common_data);
+        let left_pis = &left_proof_with_pis.public_inputs;
+        let right_pis = &right_proof_with_pis.public_inputs;
+
+        // A merge starts from the state before the steps of its left child, and its right child
+        // continues from the state after them. Both children start from the same test vector.
+        for range in ranges.inputs() {
+            for i in range.0..range.1 {
+                connect_unless(&mut builder, is_leaf, node_pis[i], left_pis[i]);
+            }
+        }
+        for i in ranges.acc_init.0..ranges.acc_init.1 {
+            connect_unless(&mut builder, is_leaf, node_pis[i], right_pis[i]);
+        }
+        for (out_range, in_range) in [
+            (ranges.end, ranges.start),
+            (ranges.acc_out, ranges.acc_in),
+            (ranges.bsk_hash_out, ranges.bsk_hash_in),
+            (ranges.lwe_hash_out, ranges.lwe_hash_in),
+        ] {
+            for (i, j) in (out_range.0..out_range.1).zip(in_range.0..in_range.1) {
+                connect_unless(&mut builder, is_leaf, left_pis[i], right_pis[j]);
+            }
+        }
+
+        // the state after the steps of a leaf, or after those of the right child of a merge,
+        // which also holds the statement digest if the last step is among them
+        let leaf_outputs = [
+            vec![leaf_end],
+            acc.flatten(),
+            bsk_hash.elements.to_vec(),
+            lwe_hash.elements.to_vec(),
+            statement_digest.elements.to_vec(),
+        ];
+        let output_ranges = [
+            ranges.end,
+            ranges.acc_out,
+            ranges.bsk_hash_out,
+            ranges.lwe_hash_out,
+            ranges.statement_digest,
+        ];
+        for (range, leaf_output) in output_ranges.into_iter().zip(leaf_outputs) {
+            for (i, leaf_target) in (range.0..range.1).zip(leaf_output) {
+                let output = builder.select(is_leaf, leaf_target, right_pis[i]);
+                builder.connect(node_pis[i], output);
+            }
+        }
+
+        for proof_with_pis in [&left_proof_with_pis, &right_proof_with_pis] {
+            builder
+                .conditionally_verify_cyclic_proof_or_dummy::<C>(is_merge, proof_with_pis, &common_data)
+                .unwrap();
+        }
+
+        let targets = NodeTargets {
+            node_pis,
+            is_leaf,
+            ggsws,
+            mask_elements,
+            left_proof_with_pis,
+            right_proof_with_pis,
+            verifier_data,
+        };
+        (builder, targets)
+    }
+
+    // Proves the PBS of `ct` with the test vector `testv`, the bootstrapping key `bsk` and the
+    // key switching key `ksk`, with `num_workers` threads; returns the output ciphertext and the
+    // proof at the root of the tree
+    pub fn prove(
+        &self,
+        ct: &[F],
+        testv: &Poly<F, D, N>,
+        bsk: &[Ggsw<F, D, N, K, ELL>],
+        ksk: &Ggsw<F, D, N, K, ELL>,
+        num_workers: NonZeroUsize,
+    ) -> Result<(Glwe<F, D, N, K>, ProofWithPublicInputs<F, C, D>)> {
+        // the GGSW and the LWE element consumed by each step, as in `PbsProver`
+        let dummy_ggsw = Ggsw::<F, D, N, K, ELL>::dummy_ct();
+        let step_ggsws: Vec<&Ggsw<F, D, N, K, ELL>> =
+            once(&dummy_ggsw).chain(bsk).chain(once(ksk)).collect();
+        let mask_elements = step_masks::<F, n>(ct, 1);
+        let acc_init = Glwe::<F, D, N, K>::trivial_ct(testv.clone()).flatten();
+        let states =
+            step_states::<F, D, N, K, ELL, LOGB>(&acc_init, &step_ggsws, &mask_elements);
+
+        let starts: Vec<usize> = (0..step_ggsws.len()).step_by(self.chunk_size).collect();
+        info!("proving {} chunks of {} steps", starts.len(), self.chunk_size);
+        let mut proofs = try_parallel_map(starts, num_workers, |start| {
+            self.prove_leaf(start, &acc_init, &states[start], &step_ggsws, &mask_elements)
+        })?;
+
+        // each level of the tree merges consecutive pairs of proofs, and carries the last proof
+        // of an odd number over to the next level
+        while proofs.len() > 1 {
+            info!("merging {} proofs", proofs.len());
+            let mut pairs = Vec::with_capacity(proofs.len() / 2);
+            let mut carried = None;
+            let mut level = proofs.into_iter();
+            while let Some(left) = level.next() {
+                match level.next() {
+                    Some(right) => pairs.push((left, right)),
+                    None => carried = Some(left),
+                }
+            }
+            proofs = try_parallel_map(pairs, num_workers, |(left, right)| {
+                self.prove_merge(&left, &right)
+            })?;
+            proofs.extend(carried);
+        }
+        let proof = proofs.pop().unwrap();
+        let acc_out = NodeRanges::new::<N, K>().acc_out;
+        Ok((Glwe::from_slice(&proof.public_inputs[acc_out.0..acc_out.1]), proof))
+    }
+
+    // proves the chunk of steps from `start`, starting from `state`
+    fn prove_leaf(
+        &self,
+        start: usize,
+        acc_init: &[F],
+        state: &StepState<F, D, N, K>,
+        step_ggsws: &[&Ggsw<F, D, N, K, ELL>],
+        mask_elements: &[F],
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        let ranges = NodeRanges::new::<N, K>();
+        let mut pw = PartialWitness::new();
+        pw.set_bool_target(self.targets.is_leaf, true);
+        let inputs = [
+            acc_init.to_vec(),
+            vec![F::from_canonical_usize(start)],
+            state.acc.flatten(),
+            state.bsk_hash.elements.to_vec(),
+            state.lwe_hash.elements.to_vec(),
+        ];
+        for (range, values) in ranges.inputs().into_iter().zip(inputs) {
+            pw.set_target_arr(&self.targets.node_pis[range.0..range.1], &values);
+        }
+        // the steps after the key switch (in the last chunk) are not proven
+        self.assign_steps(&mut pw, &step_ggsws[start..], &mask_elements[start..]);
+        let base_proof = cyclic_base_proof(
+            &self.circuit_data.common,
+            &self.circuit_data.verifier_only,
+            Default::default(),
+        );
+        let end = (start + self.chunk_size).min(step_ggsws.len());
+        self.prove_node(pw, &base_proof, &base_proof, &format!("prove steps {start}..{end}"))
+    }
+
+    // proves the steps of two proofs of consecutive chunks of steps
+    fn prove_merge(
+        &self,
+        left: &ProofWithPublicInputs<F, C, D>,
+        right: &ProofWithPublicInputs<F, C, D>,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        let ranges = NodeRanges::new::<N, K>();
+        let mut pw = PartialWitness::new();
+        pw.set_bool_target(self.targets.is_leaf, false);
+        for range in ranges.inputs() {
+            pw.set_target_arr(
+                &self.targets.node_pis[range.0..range.1],
+                &left.public_inputs[range.0..range.1],
+            );
+        }
+        self.assign_steps(&mut pw, &[], &[]);
+        let steps = |proof: &ProofWithPublicInputs<F, C, D>, range: (usize, usize)| {
+            proof.public_inputs[range.0].to_canonical_u64()
+        };
+        let name = format!(
+            "merge steps {}..{}",
+            steps(left, ranges.start),
+            steps(right, ranges.end)
+        );
+        self.prove_node(pw, left, right, &name)
+    }
+
+    // assigns the GGSWs and the LWE elements of the steps of a leaf, and dummy ones to the
+    // steps left over
+    fn assign_steps(
+        &self,
+        pw: &mut PartialWitness<F>,
+        step_ggsws: &[&Ggsw<F, D, N, K, ELL>],
+        mask_elements: &[F],
+    ) {
+        let dummy_ggsw = Ggsw::<F, D, N, K, ELL>::dummy_ct();
+        for (i, (ggsw, &mask_element)) in self
+            .targets
+            .ggsws
+            .iter()
+            .zip(&self.targets.mask_elements)
+            .enumerate()
+        {
+            ggsw.assign(pw, step_ggsws.get(i).copied().unwrap_or(&dummy_ggsw));
+            pw.set_target(mask_element, mask_elements.get(i).copied().unwrap_or(F::ZERO));
+        }
+    }
+
+    fn prove_node(
+        &self,
+        mut pw: PartialWitness<F>,
+        left: &ProofWithPublicInputs<F, C, D>,
+        right: &ProofWithPublicInputs<F, C, D>,
+        name: &str,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        pw.set_proof_with_pis_target(&self.targets.left_proof_with_pis, left);
+        pw.set_proof_with_pis_target(&self.targets.right_proof_with_pis, right);
+        pw.set_verifier_data_target(&self.targets.verifier_data, &self.circuit_data.verifier_only);
+        let mut timing = TimingTree::new(name, Level::Info);
+        let proof = prove::<F, C, D>(
+            &self.circuit_data.prover_only,
+            &self.circuit_data.common,
+            pw,
+            &mut timing,
+        )
+        .with_context(|| format!("failed to {name}"))?;
+        timing.print();
+        Ok(proof)
+    }
+}
+
+// Same as `verified_pbs`, but the steps are proven in chunks of `chunk_size` on `num_workers`
+// threads and merged in a tree (see `PbsTreeProver`). The proof is for the node circuit, and is
+// checked with `verify_pbs_tree`.
+pub fn verified_pbs_tree<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F> + 'static,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+    ksk: &Ggsw<F, D, N, K, ELL>,
+    chunk_size: NonZeroUsize,
+    num_workers: NonZeroUsize,
+) -> Result<PbsTreeOutput<F, C, D, N, K>>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+{
+    let prover = PbsTreeProver::<F, C, D, n, N, K, ELL, LOGB>::new(chunk_size);
+    let (out_ct, proof) = prover.prove(ct, testv, bsk, ksk, num_workers)?;
+    Ok((out_ct, proof, prover.circuit_data))
+}
+
+pub fn verify_pbs_tree<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F> + 'static,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+    const ELL: usize,
+    const LOGB: usize,
+>(
+    out_ct: &Glwe<F, D, N, K>,
+    ct: &[F],
+    testv: &Poly<F, D, N>,
+    bsk: &[Ggsw<F, D, N, K, ELL>],
+    ksk: &Ggsw<F, D, N, K, ELL>,
+    proof: &ProofWithPublicInputs<F, C, D>,
+    cd: &VerifierCircuitData<F, C, D>,
+) -> Result<()>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+{
+    let statement = PbsStatement::<F, D, N, K>::new::<n, ELL>(&PbsInputs::new(ct, testv, bsk, ksk));
+    verify_pbs_tree_digest::<F, C, D, n, N, K>(out_ct, &statement.digest(), proof, cd)
+}
+
+// Verifies the proof at the root of the tree of `PbsTreeProver` against a statement digest,
+// which is the same as that of `verify_pbs` for the same PBS
+pub fn verify_pbs_tree_digest<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F> + 'static,
+    const D: usize,
+    const n: usize,
+    const N: usize,
+    const K: usize,
+>(
+    out_ct: &Glwe<F, D, N, K>,
+    digest: &HashOut<F>,
+    proof: &ProofWithPublicInputs<F, C, D>,
+    cd: &VerifierCircuitData<F, C, D>,
+) -> Result<()>
+where
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+{
+    let ranges = NodeRanges::new::<N, K>();
+    let pis = |range: (usize, usize)| &proof.public_inputs[range.0..range.1];
+    ensure!(
+        *out_ct == Glwe::from_slice(pis(ranges.acc_out)),
+        "the proof is not for this output ciphertext"
+    );
+
+    // the root covers all the steps, starting from the test vector and empty hash chains
+    ensure!(pis(ranges.start) == [F::ZERO], "proof does not start at the first step");
+    ensure!(
+        pis(ranges.end) == [F::from_canonical_usize(n + 2)],
+        "proof does not end at the last step"
+    );
+    ensure!(
+        pis(ranges.acc_in) == pis(ranges.acc_init),
+        "proof does not start from the initial accumulator"
+    );
+    ensure!(
+        pis(ranges.bsk_hash_in)
+            .iter()
+            .chain(pis(ranges.lwe_hash_in))
+            .all(|x| x.is_zero()),
+        "proof does not start from empty hash chains"
+    );
+
+    cd.verify(proof.clone()).context("invalid proof")?;
+    check_cyclic_proof_verifier_data(proof, &cd.verifier_only, &cd.common)
+        .context("the proof is not for the node circuit")?;
+    let claimed_digest = HashOut::try_from(pis(ranges.statement_digest))?;
+    ensure!(*digest == claimed_digest, "proof is not for this PBS statement");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ntt::params::N;
+    use crate::vtfhe::crypto::pbs;
+    use crate::vtfhe::crypto::rng::test_rng;
+    use crate::vtfhe::ivc_based_vpbs::tests::{test_fixture, TestFixture, C, D, ELL, F, K, LOGB};
+
+    #[test]
+    fn test_pbs_tree() {
+        const n: usize = 1;
+
+        let rng = &mut test_rng();
+        let TestFixture { s_lwe, bsk, ksk, testv, delta, .. } = test_fixture::<n, N>(rng);
+        let ct = s_lwe.encrypt(&delta, 0f64, rng);
+
+        // the n + 2 = 3 steps are proven one by one, so the tree merges the first two and
+        // carries the third over to the next level
+        let chunk_size = NonZeroUsize::new(1).unwrap();
+        let num_workers = NonZeroUsize::new(2).unwrap();
+        let (out_ct, proof, cd) = verified_pbs_tree::<F, C, D, n, N, K, ELL, LOGB>(
+            &ct, &testv, bsk.ggsws(), ksk.ggsw(), chunk_size, num_workers,
+        )
+        .unwrap();
+        assert_eq!(out_ct, pbs::<F, D, n, N, K, ELL, LOGB>(&ct, &testv, bsk.ggsws(), ksk.ggsw()));
+        verify_pbs_tree::<F, C, D, n, N, K, ELL, LOGB>(
+            &out_ct,
+            &ct,
+            &testv,
+            bsk.ggsws(),
+            ksk.ggsw(),
+            &proof,
+            &cd.verifier_data(),
+        )
+        .unwrap();
+    }
+}
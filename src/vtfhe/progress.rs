@@ -0,0 +1,167 @@
+use std::fs;
+use std::io::{stderr, IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::json;
+
+// The progress of a PBS proof after one of its recursive proofs, see `ProgressObserver`
+#[derive(Clone, Debug)]
+pub struct StepProgress {
+    // the index of the job in `PbsProver::prove_many`, if the PBS is one of several
+    pub job: Option<usize>,
+    // the number of steps of the PBS proven so far, out of `total_steps`
+    pub step: usize,
+    pub total_steps: usize,
+    // the time taken by the latest proof
+    pub step_duration: Duration,
+    // the estimated time left, at the average rate of the steps proven so far in this run
+    pub eta: Duration,
+    // the resident memory of the process in bytes, if it can be read
+    pub memory: Option<u64>,
+}
+
+// Notified after each recursive proof of a PBS, e.g. to show its progress or to detect a stalled
+// proof. Observers are shared by the workers of `PbsProver::prove_many`, so the updates of
+// several jobs can arrive concurrently.
+pub trait ProgressObserver: Send + Sync {
+    fn on_step(&self, progress: &StepProgress);
+}
+
+// ignores the progress
+pub struct NoProgress;
+
+impl ProgressObserver for NoProgress {
+    fn on_step(&self, _progress: &StepProgress) {}
+}
+
+// A progress bar on stderr, redrawn in place on a terminal and printed one line per step
+// otherwise (e.g. to a log file)
+pub struct ProgressBar {
+    width: usize,
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        ProgressBar { width: 40 }
+    }
+}
+
+impl ProgressObserver for ProgressBar {
+    fn on_step(&self, progress: &StepProgress) {
+        let filled = self.width * progress.step / progress.total_steps.max(1);
+        let job = progress.job.map_or(String::new(), |job| format!("job {job} "));
+        let memory = progress
+            .memory
+            .map_or(String::new(), |memory| format!(", {} MiB", memory >> 20));
+        let line = format!(
+            "{job}[{}{}] {}/{} steps, {:.1?} per step, ETA {:.0?}{memory}",
+            "#".repeat(filled),
+            " ".repeat(self.width - filled),
+            progress.step,
+            progress.total_steps,
+            progress.step_duration,
+            progress.eta,
+        );
+        if !stderr().is_terminal() {
+            eprintln!("{line}");
+        } else if progress.step < progress.total_steps {
+            eprint!("\r{line}\x1b[K");
+        } else {
+            eprintln!("\r{line}\x1b[K");
+        }
+    }
+}
+
+// Writes each update as a line of JSON, e.g. to stdout for an orchestrator:
+// {"job":null,"step":12,"total_steps":730,"step_secs":4.2,"eta_secs":3015.6,"memory_bytes":...}
+pub struct JsonLinesProgress<W: Write + Send> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write + Send> JsonLinesProgress<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLinesProgress {
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl<W: Write + Send> ProgressObserver for JsonLinesProgress<W> {
+    fn on_step(&self, progress: &StepProgress) {
+        let line = json!({
+            "job": progress.job,
+            "step": progress.step,
+            "total_steps": progress.total_steps,
+            "step_secs": progress.step_duration.as_secs_f64(),
+            "eta_secs": progress.eta.as_secs_f64(),
+            "memory_bytes": progress.memory,
+        });
+        // the progress is best effort, so a closed output does not fail the proof
+        let mut writer = self.writer.lock().unwrap();
+        let _ = writeln!(writer, "{line}").and_then(|()| writer.flush());
+    }
+}
+
+// the resident set size of the process, from /proc on Linux
+pub fn resident_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let kib = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib << 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_lines_progress() {
+        let mut output = Vec::new();
+        {
+            let observer = JsonLinesProgress::new(&mut output);
+            for step in 1..=2 {
+                observer.on_step(&StepProgress {
+                    job: Some(3),
+                    step,
+                    total_steps: 2,
+                    step_duration: Duration::from_millis(1500),
+                    eta: Duration::from_secs(2 - step as u64),
+                    memory: None,
+                });
+            }
+        }
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            json!({
+                "job": 3,
+                "step": 1,
+                "total_steps": 2,
+                "step_secs": 1.5,
+                "eta_secs": 1.0,
+                "memory_bytes": null,
+            })
+        );
+        assert_eq!(lines[1]["eta_secs"], 0.0);
+    }
+
+    #[test]
+    fn test_resident_memory() {
+        if cfg!(target_os = "linux") {
+            assert!(resident_memory().unwrap() > 0);
+        }
+    }
+}